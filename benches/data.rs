@@ -0,0 +1,81 @@
+use std::{hint::black_box, path::Path};
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use dir_link::data::{dir::LinkDir, dirset::LinkDirSet, index::PathIndex, link::Link};
+
+const SIZES: [usize; 2] = [10_000, 100_000];
+// 每个文件夹中的链接数量
+const FOLDER_SIZE: usize = 1_000;
+
+fn generate(links: usize) -> LinkDirSet {
+    let mut data = LinkDirSet::new();
+    for folder in 0..links / FOLDER_SIZE {
+        let mut dir = LinkDir::builder(&format!("folder{folder}")).unwrap();
+        for idx in 0..FOLDER_SIZE {
+            let path = format!("/srv/{folder}/{idx}");
+            dir.push(Link::builder(&format!("link{idx}"), Path::new(&path)).unwrap())
+                .unwrap();
+        }
+        data.push(dir).unwrap();
+    }
+    data
+}
+
+fn lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup");
+    for size in SIZES {
+        let data = generate(size);
+        let folder = size / FOLDER_SIZE - 1;
+        let target = format!("folder{folder}/link{}", FOLDER_SIZE - 1);
+        group.bench_with_input(
+            BenchmarkId::new("identifier", size),
+            &target,
+            |b, target| b.iter(|| data.find_link(black_box(target)).unwrap()),
+        );
+
+        let index = PathIndex::new(&data);
+        let path = format!("/srv/{folder}/{}", FOLDER_SIZE - 1);
+        group.bench_with_input(
+            BenchmarkId::new("path", size),
+            Path::new(&path),
+            |b, path| b.iter(|| index.get(black_box(path)).len()),
+        );
+        group.bench_with_input(BenchmarkId::new("path_index", size), &data, |b, data| {
+            b.iter(|| PathIndex::new(data))
+        });
+    }
+    group.finish();
+}
+
+fn edit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("edit");
+    for size in SIZES {
+        group.bench_function(BenchmarkId::new("remove_first", size), |b| {
+            b.iter_batched_ref(
+                || generate(size),
+                |data| data.dir_mut(0).remove(0),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn serde(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serde");
+    group.sample_size(20);
+    for size in SIZES {
+        let data = generate(size);
+        group.bench_with_input(BenchmarkId::new("save", size), &data, |b, data| {
+            b.iter(|| serde_json::to_vec(&data.personal()).unwrap())
+        });
+        let json = serde_json::to_vec(&data).unwrap();
+        group.bench_with_input(BenchmarkId::new("load", size), &json, |b, json| {
+            b.iter(|| serde_json::from_slice::<LinkDirSet>(json).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lookup, edit, serde);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+use std::path::Path;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use dir_link::{
+    app::App,
+    data::{dir::LinkDir, dirset::LinkDirSet, link::Link},
+};
+use ratatui::{Terminal, backend::TestBackend};
+
+// 所有链接放在同一个文件夹中，渲染时链接列表最长
+fn generate(links: usize) -> LinkDirSet {
+    let mut dir = LinkDir::builder("folder").unwrap();
+    for idx in 0..links {
+        let path = format!("/srv/{idx}");
+        dir.push(Link::builder(&format!("link{idx}"), Path::new(&path)).unwrap())
+            .unwrap();
+    }
+    let mut data = LinkDirSet::new();
+    data.push(dir).unwrap();
+    data
+}
+
+fn render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    for size in [10_000, 100_000] {
+        let mut app = App::new(generate(size));
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        group.bench_function(BenchmarkId::new("frame", size), |b| {
+            b.iter(|| app.draw(&mut terminal).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, render);
+criterion_main!(benches);
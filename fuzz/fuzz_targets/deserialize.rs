@@ -0,0 +1,26 @@
+#![no_main]
+
+use dir_link::data::{dir::LinkDir, dirset::LinkDirSet, link::Link};
+use libfuzzer_sys::fuzz_target;
+
+// 与`Link::builder`一样，标识符不为空，路径是绝对路径或者路径模板
+fn is_valid(link: &Link) -> bool {
+    !link.identifier().is_empty() && (link.path().is_absolute() || link.is_template())
+}
+
+// 自定义的`Deserialize`只能产生标识符集合一致的数据，并且写回后能原样读取
+fuzz_target!(|bytes: &[u8]| {
+    if let Ok(data) = serde_json::from_slice::<LinkDirSet>(bytes) {
+        assert!(data.is_consistent());
+        assert!(data.iter().flat_map(|dir| dir.iter()).all(is_valid));
+        let json = serde_json::to_vec(&data).unwrap();
+        assert_eq!(serde_json::from_slice::<LinkDirSet>(&json).unwrap(), data);
+    }
+    if let Ok(dir) = serde_json::from_slice::<LinkDir>(bytes) {
+        assert!(dir.is_consistent());
+        assert!(dir.iter().all(is_valid));
+    }
+    if let Ok(link) = serde_json::from_slice::<Link>(bytes) {
+        assert!(is_valid(&link));
+    }
+});
@@ -0,0 +1,55 @@
+# dir_link integration for bash
+# Add `eval "$(dir_link init bash)"` to ~/.bashrc
+
+# Fields: dir_link, version, action, status, payload...
+function __dir_link_apply() {
+    [ "$1" = "dir_link" ] && [ "$2" = "1" ] || return 1
+    case "$3" in
+        cd) [ "$5" = "$PWD" ] || builtin cd -- "$5" || return ;;
+        pushd) builtin pushd -- "$5" > /dev/null || return ;;
+        print) printf '%s\n' "$5" ;;
+        exec-command)
+            (builtin cd -- "$5" && eval "$6")
+            return
+            ;;
+        open-editor)
+            builtin cd -- "$5" && ${VISUAL:-${EDITOR:-vi}} .
+            return
+            ;;
+    esac
+    return "$4"
+}
+
+function {{cmd}}() {
+    local tmp ret field
+    local -a result=()
+    tmp="$(mktemp -t "dir_link-result.XXXXXX")" || return
+    if [ "$#" -gt 0 ] && [ "${1#-}" = "$1" ] && [ "$1" != "go" ]; then
+        command dir_link "$tmp" go "$1"
+    else
+        command dir_link "$tmp" "$@"
+    fi
+    ret=$?
+    while IFS= read -r -d '' field; do
+        result+=("$field")
+    done < "$tmp"
+    rm -f -- "$tmp"
+    [ "${#result[@]}" -gt 0 ] || return "$ret"
+    __dir_link_apply "${result[@]}"
+}
+
+function __dir_link_widget() {
+    {{cmd}} < /dev/tty
+}
+
+function __dir_link_complete() {
+    # `dlk folder/link` or `dlk go folder/link`
+    [ "$COMP_CWORD" -eq 1 ] || { [ "$COMP_CWORD" -eq 2 ] && [ "${COMP_WORDS[1]}" = "go" ]; } || return
+    local IFS=$'\n'
+    COMPREPLY=($(compgen -W "$(command dir_link list 2> /dev/null)" -- "${COMP_WORDS[COMP_CWORD]}"))
+}
+
+if [[ $- == *i* ]]; then
+    bind -x '"\C-g": __dir_link_widget'
+fi
+complete -F __dir_link_complete {{cmd}}
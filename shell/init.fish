@@ -0,0 +1,50 @@
+# dir_link integration for fish
+# Add `dir_link init fish | source` to ~/.config/fish/config.fish
+
+# Fields: dir_link, version, action, status, payload...
+function __dir_link_apply
+    test (count $argv) -ge 5; and test "$argv[1]" = dir_link; and test "$argv[2]" = 1; or return 1
+    switch $argv[3]
+        case cd
+            test "$argv[5]" = "$PWD"; or builtin cd -- $argv[5]; or return
+        case pushd
+            pushd $argv[5]; or return
+        case print
+            printf '%s\n' $argv[5]
+        case exec-command
+            fish -C "builtin cd -- "(string escape -- $argv[5]) -c $argv[6]
+            return
+        case open-editor
+            set -l editor vi
+            set -q EDITOR; and set editor $EDITOR
+            set -q VISUAL; and set editor $VISUAL
+            builtin cd -- $argv[5]; and eval $editor .
+            return
+    end
+    return $argv[4]
+end
+
+function {{cmd}}
+    set -l tmp (mktemp -t "dir_link-result.XXXXXX"); or return
+    if test (count $argv) -gt 0; and not string match -q -- '-*' $argv[1]; and test $argv[1] != go
+        command dir_link $tmp go $argv[1]
+    else
+        command dir_link $tmp $argv
+    end
+    set -l ret $status
+    set -l result (string split0 < $tmp)
+    command rm -f -- $tmp
+    test (count $result) -gt 0; or return $ret
+    __dir_link_apply $result
+end
+
+function __dir_link_widget
+    {{cmd}}
+    commandline -f repaint
+end
+
+bind \cg __dir_link_widget
+bind -M insert \cg __dir_link_widget 2> /dev/null
+# `dlk folder/link` or `dlk go folder/link`
+complete -c {{cmd}} -f -n __fish_is_first_arg -a '(command dir_link list 2> /dev/null)'
+complete -c {{cmd}} -f -n '__fish_seen_subcommand_from go; and test (count (commandline -opc)) -eq 2' -a '(command dir_link list 2> /dev/null)'
@@ -1,26 +1,40 @@
 use std::io;
+use std::path::PathBuf;
+use std::time::Instant;
 
 use ratatui::prelude::*;
 use ratatui::widgets::{Clear, ListState, TableState};
-use ratatui::{
-    Terminal,
-    crossterm::event::{self, Event},
-};
+use ratatui::{Terminal, crossterm::event::Event};
 
-use crate::app::data::{AppData, AppOption, DataTransfer, RuntimeError};
+use crate::app::data::{AppData, AppOption, DataTransfer, RuntimeError, Toast};
+use crate::app::event::{AppEvent, EventLoop, EventSource, TaskResult};
+use crate::app::external::ExternalAction;
 use crate::app::float::Float;
-use crate::app::float::warning::CorruptDataWarningState;
-use crate::app::normal::FolderNormalState;
+use crate::app::float::import::ImportPreviewState;
+use crate::app::float::warning::{CorruptDataWarningState, WarningState};
+use crate::app::normal::{FolderNormalState, LinkNormalState};
+use crate::app::operation::{Applied, PendingOperation};
 use crate::app::state::{AppState, NormalState};
-use crate::data::dirset::LinkDirSet;
+use crate::app::worker::{Query, QueryKind, WorkerPool};
+use crate::data::{dirset::LinkDirSet, link::Link};
+use crate::import::Imported;
+use crate::layer::Layers;
+use crate::profile::DataSource;
+use crate::term::ViewportMode;
 use crate::ui;
+use crate::{get_data, try_save};
 
 pub mod data;
+pub mod event;
+pub mod external;
 pub mod float;
 pub mod key;
 pub mod message;
 pub mod normal;
+pub mod operation;
+pub mod record;
 pub mod state;
+pub mod worker;
 
 pub struct App {
     pub state: AppState,
@@ -28,11 +42,25 @@ pub struct App {
     pub option: AppOption,
     pub cache: AppData,
     float: Vec<Float>,
+    events: EventLoop,
+    // 第一次查询文件系统时创建
+    workers: Option<WorkerPool>,
+    external: Option<ExternalAction>,
 }
 
 impl Widget for &mut App {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        ui::render_main_border(area, buf);
+        self.cache.sync_area(area);
+        let profile = self.option.source.as_ref().and_then(|s| s.profile_name());
+        ui::render_main_border(profile, area, buf);
+        if self.state.is_link()
+            && let Some(path) = self.selected_path()
+        {
+            ui::render_link_details(&path, &self.cache.queries, area, buf);
+        }
+        if let Some(toast) = &self.cache.toast {
+            ui::render_toast(toast, area, buf);
+        }
 
         let chunks = Layout::default()
             .margin(1)
@@ -55,33 +83,28 @@ impl Widget for &mut App {
         for float in &mut self.float {
             match float {
                 Float::FolderEdit(state) => {
-                    let area = ui::common::centered_rect(50, 25, area);
+                    let area = ui::common::centered_rect_min(50, 25, 6, area);
                     Clear.render(area, buf);
                     ui::render_folder_edit(state, area, buf, &mut self.cache.cursor);
                 }
                 Float::LinkEdit(state) => {
-                    let area = ui::common::centered_rect(60, 30, area);
+                    let area = ui::common::centered_rect_min(60, 30, 12, area);
                     Clear.render(area, buf);
                     ui::render_link_edit(state, area, buf, &mut self.cache.cursor);
                 }
-                Float::FolderDeleteConfirm(state) => {
-                    let area = ui::common::centered_rect(50, 30, area);
-                    Clear.render(area, buf);
-                    ui::render_folder_delete_confirm_float(state, area, buf)
-                }
-                Float::LinkDeleteConfirm(state) => {
-                    let area = ui::common::centered_rect(50, 30, area);
+                Float::Confirm(state) => {
+                    let area = ui::common::centered_rect_min(50, 30, 8, area);
                     Clear.render(area, buf);
-                    ui::render_link_delete_confirm_float(state, area, buf)
+                    ui::render_confirm_float(state, area, buf)
                 }
                 Float::Warning(state) => {
-                    let area = ui::common::centered_rect(40, 25, area);
+                    let area = ui::common::centered_rect_min(40, 25, 5, area);
                     Clear.render(area, buf);
                     ui::render_warning_float(state, area, buf);
                 }
                 Float::FolderSaveConfirm(state) => {
-                    let edit_area = ui::common::centered_rect(50, 25, area);
-                    let area = ui::common::centered_rect(50, 30, area);
+                    let edit_area = ui::common::centered_rect_min(50, 25, 6, area);
+                    let area = ui::common::centered_rect_min(50, 30, 8, area);
                     Clear.render(area, buf);
                     ui::render_folder_save_confirm_float(
                         state,
@@ -92,8 +115,8 @@ impl Widget for &mut App {
                     );
                 }
                 Float::LinkSaveConfirm(state) => {
-                    let edit_area = ui::common::centered_rect(60, 30, area);
-                    let area = ui::common::centered_rect(50, 30, area);
+                    let edit_area = ui::common::centered_rect_min(60, 30, 12, area);
+                    let area = ui::common::centered_rect_min(50, 30, 8, area);
                     Clear.render(area, buf);
                     ui::render_link_save_confirm_float(
                         state,
@@ -104,12 +127,32 @@ impl Widget for &mut App {
                     );
                 }
                 Float::CorruptDataWarning(state) => {
-                    let area = ui::common::centered_rect(50, 40, area);
+                    let area = ui::common::centered_rect_min(50, 40, 10, area);
                     Clear.render(area, buf);
                     ui::render_corrupt_data_warning_float(state, area, buf);
                 }
+                Float::BulkEditError(state) => {
+                    let area = ui::common::centered_rect_min(50, 40, 10, area);
+                    Clear.render(area, buf);
+                    ui::render_bulk_edit_error_float(state, area, buf);
+                }
+                Float::ImportPreview(state) => {
+                    let area = ui::common::centered_rect_min(70, 60, 10, area);
+                    Clear.render(area, buf);
+                    ui::render_import_preview_float(state, area, buf);
+                }
+                Float::Command(state) => {
+                    let area = ui::common::centered_rect_min(60, 25, 6, area);
+                    Clear.render(area, buf);
+                    ui::render_command(state, area, buf, &mut self.cache.cursor);
+                }
+                Float::Profile(state) => {
+                    let area = ui::common::centered_rect_min(40, 40, 6, area);
+                    Clear.render(area, buf);
+                    ui::render_profile_float(state, area, buf);
+                }
                 Float::Help(state) => {
-                    let area = ui::common::centered_rect(50, 50, area);
+                    let area = ui::common::centered_rect_min(50, 50, 8, area);
                     Clear.render(area, buf);
                     ui::render_help_float(state, area, buf);
                 }
@@ -119,8 +162,14 @@ impl Widget for &mut App {
 }
 
 impl App {
-    #[inline]
     pub fn set_state(&mut self, state: AppState) {
+        if self.state.name() != state.name() {
+            tracing::debug!(
+                from = self.state.name(),
+                to = state.name(),
+                "state transition"
+            );
+        }
         self.state = state;
     }
 
@@ -129,14 +178,20 @@ impl App {
         self.float.pop()
     }
 
-    #[inline]
+    /// 显示一个新的浮窗，警告和错误同时写入日志
     pub fn add_float(&mut self, float: Float) {
+        match &float {
+            Float::Warning(state) => tracing::warn!(message = state.message()),
+            Float::BulkEditError(state) => tracing::warn!(message = state.message()),
+            Float::CorruptDataWarning(state) => tracing::error!(message = state.message()),
+            _ => {}
+        }
+        tracing::debug!(float = float.name(), depth = self.float.len(), "push float");
         self.float.push(float);
     }
 
-    #[inline]
     pub fn extend_float<I: IntoIterator<Item = Float>>(&mut self, floats: I) {
-        self.float.extend(floats);
+        floats.into_iter().for_each(|float| self.add_float(float));
     }
 
     #[inline]
@@ -150,17 +205,179 @@ impl App {
             data,
             float: Vec::new(),
             cache: AppData::new(),
-            option: AppOption { save: true },
+            option: AppOption::new(),
+            events: EventLoop::default(),
+            workers: None,
+            external: None,
+        }
+    }
+
+    pub fn with_viewport(mut self, viewport: ViewportMode) -> Self {
+        self.option.viewport = viewport;
+        self
+    }
+
+    /// 替换终端事件的来源，用于测试或者重放
+    pub fn with_event_source<S: EventSource + 'static>(mut self, source: S) -> Self {
+        self.events = EventLoop::default().with_source(source);
+        self
+    }
+
+    pub fn with_source(mut self, source: DataSource) -> Self {
+        self.option.source = Some(source);
+        self
+    }
+
+    /// 切换配置时使用`dir`中的副本，不会读写原来的配置
+    pub fn with_sandbox(mut self, dir: PathBuf) -> Self {
+        self.option.sandbox = Some(dir);
+        self
+    }
+
+    /// 切换配置时在`dir`而不是默认的数据目录中查找配置
+    pub fn with_data_dir(mut self, dir: PathBuf) -> Self {
+        self.option.data_dir = Some(dir);
+        self
+    }
+
+    /// 合并只读的共享数据，之后重新加载个人数据时也会再次合并
+    pub fn with_layers(mut self, layers: Layers) -> Self {
+        self.option.layers = layers;
+        self.merge_layers();
+        self
+    }
+
+    /// 无法读取的共享文件只显示警告，不影响个人数据
+    pub(crate) fn merge_layers(&mut self) {
+        for err in self.option.layers.merge_into(&mut self.data) {
+            let msg = format!("Failed to read shared links: {err}");
+            self.add_float(Float::Warning(WarningState::new(msg)));
+        }
+    }
+
+    /// 替换为重新读取的数据，按照标识符恢复选中的文件夹和链接
+    pub fn reload_data(&mut self, data: LinkDirSet) {
+        let folder = self
+            .state
+            .folder_list_state()
+            .and_then(|s| s.selected())
+            .and_then(|idx| self.data.get(idx))
+            .map(|dir| dir.identifier().to_string());
+        let link = self
+            .selected_link()
+            .map(|link| link.identifier().to_string());
+        let is_link = self.state.is_link();
+
+        self.data = data;
+        self.merge_layers();
+        let state = match folder.and_then(|folder| self.data.index_of(&folder)) {
+            Some(idx) if is_link => {
+                let link = link.and_then(|link| self.data[idx].index_of(&link));
+                NormalState::Link(LinkNormalState::with_selected(idx, Some(link.unwrap_or(0))))
+            }
+            Some(idx) => NormalState::Folder(FolderNormalState::with_selected(Some(idx))),
+            None => NormalState::Folder(FolderNormalState::new()),
+        };
+        self.set_state(AppState::Normal(Box::new(state)));
+    }
+
+    /// 保存当前配置的数据后加载另一个配置，加载失败时保留当前的数据
+    pub fn switch_profile(&mut self, name: &str) {
+        let source = match &self.option.data_dir {
+            Some(dir) => DataSource::profile_in(dir, name),
+            None => DataSource::profile(name),
+        };
+        let result = source.and_then(|source| {
+            let source = match &self.option.sandbox {
+                Some(dir) => source.sandbox(dir)?,
+                None => source,
+            };
+            if let Some(current) = &self.option.source {
+                try_save(self.option.save, current.path(), &mut self.data)?;
+            }
+            source.create_dir()?;
+            let data = get_data(source.path())?;
+            Ok((source, data))
+        });
+        match result {
+            Ok((source, data)) => {
+                tracing::info!(profile = name, path = %source.path().display(), "switch profile");
+                self.data = data;
+                self.merge_layers();
+                self.option.source = Some(source);
+                self.option.save = true;
+                self.set_state(AppState::new());
+                self.toast(format!("Switched to profile `{name}`"));
+            }
+            Err(err) => {
+                let msg = format!("Failed to switch profile: {err}");
+                self.add_float(Float::Warning(WarningState::new(msg)));
+            }
         }
     }
 
-    fn check_corrept_data(&mut self, error: &mut RuntimeError) {
+    /// 显示导入数据的预览，没有需要导入的链接时只显示提示
+    pub fn with_import(mut self, imported: Imported) -> Self {
+        let skipped = imported.skipped.len();
+        let items = crate::import::prepare(&self.data, vec![imported.dir]);
+        if items.is_empty() {
+            match skipped {
+                0 => self.toast("Nothing to import"),
+                _ => self.toast(format!("Nothing to import, {skipped} skipped")),
+            }
+        } else {
+            let state = ImportPreviewState::new(items).with_skipped(skipped);
+            self.add_float(Float::ImportPreview(state));
+        }
+        self
+    }
+
+    /// 执行确认后的操作并调整选中的条目，目标已经不存在时显示警告
+    pub fn apply(&mut self, operation: PendingOperation) {
+        tracing::info!(%operation, "apply");
+        let applied = match operation::apply(&mut self.data, &operation) {
+            Ok(applied) => applied,
+            Err(err) => {
+                self.add_float(Float::Warning(WarningState::new(err.to_string())));
+                return;
+            }
+        };
+        match (applied, &mut self.state) {
+            (Applied::FolderRemoved(idx), AppState::Normal(part)) => {
+                if let NormalState::Folder(state) = &mut **part {
+                    state.select(Some(idx.min(self.data.len().saturating_sub(1))));
+                }
+                self.toast("Folder deleted");
+            }
+            (Applied::LinkRemoved { folder, link }, AppState::Normal(part)) => {
+                if let NormalState::Link(state) = &mut **part
+                    && state.folder_index() == folder
+                {
+                    state.select(Some(link.min(self.data[folder].len().saturating_sub(1))));
+                }
+                self.toast("Link deleted");
+            }
+            _ => {}
+        }
+    }
+
+    /// 在下一次绘制之前执行需要离开TUI的操作
+    #[inline]
+    pub fn set_external(&mut self, action: ExternalAction) {
+        self.external = Some(action);
+    }
+
+    /// 显示一条短暂的提示，超时后在下一次tick时消失
+    pub fn toast<S: Into<String>>(&mut self, message: S) {
+        self.cache.toast = Some(Toast::new(message));
+    }
+
+    pub(crate) fn check_corrept_data(&mut self, error: &mut RuntimeError) {
         if let Some(err) = &error.read_data {
             self.option.save = false;
-            self.float
-                .push(Float::CorruptDataWarning(CorruptDataWarningState::new(
-                    err.to_string(),
-                )));
+            self.add_float(Float::CorruptDataWarning(CorruptDataWarningState::new(
+                err.to_string(),
+            )));
             error.read_data = None;
         }
     }
@@ -174,16 +391,16 @@ impl App {
         self.check_corrept_data(&mut runtime);
 
         loop {
-            terminal.draw(|f| {
-                f.render_widget(&mut self, f.area());
-                if let Some(pos) = self.cache.cursor.get_pos() {
-                    f.set_cursor_position(pos);
-                }
-            })?;
-            self.handle_event()?;
+            if let Some(action) = self.external.take() {
+                external::run_external(&mut self, terminal, action)?;
+            }
+            self.draw(terminal)?;
+            self.submit_queries();
+            let event = self.events.next_event()?;
+            self.handle_event(event);
             if let AppState::Quit(data) = &mut self.state {
                 // TODO: 性能损耗，之后尝试改进
-                data_transfer.link = data.link.take();
+                data_transfer.action = data.action.take();
                 break;
             }
         }
@@ -191,15 +408,106 @@ impl App {
             config.save = self.option.save;
         }
         data_transfer.data = Some(self.data);
+        data_transfer.source = self.option.source;
         Ok(data_transfer)
     }
 
-    pub fn handle_event(&mut self) -> io::Result<()> {
-        match event::read()? {
-            Event::Key(key) => key::handle_key_event(self, key),
-            Event::Mouse(_) => {} // TODO: handle mouse event
-            _ => {}
-        }
+    pub fn draw<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        terminal.draw(|f| {
+            f.render_widget(&mut *self, f.area());
+            if let Some(pos) = self.cache.cursor.get_pos() {
+                f.set_cursor_position(pos);
+            }
+        })?;
         Ok(())
     }
+
+    pub fn handle_event(&mut self, event: AppEvent) {
+        match event {
+            AppEvent::Terminal(Event::Key(key)) => key::handle_key_event(self, key),
+            AppEvent::Terminal(Event::Paste(text)) => key::handle_paste_event(self, &text),
+            AppEvent::Terminal(Event::Mouse(_)) => {} // TODO: handle mouse event
+            // 下一次绘制时终端会自动调整大小，此处只需要重新计算光标位置
+            AppEvent::Terminal(Event::Resize(_, _)) => self.cache.cursor.outdate(),
+            AppEvent::Terminal(_) => {}
+            AppEvent::Tick => {
+                if self
+                    .cache
+                    .toast
+                    .as_ref()
+                    .is_some_and(|toast| toast.is_expired(Instant::now()))
+                {
+                    self.cache.toast = None;
+                }
+            }
+            AppEvent::Tasks(results) => results
+                .into_iter()
+                .for_each(|result| self.handle_task_result(result)),
+        }
+    }
+
+    pub fn handle_task_result(&mut self, result: TaskResult) {
+        match result {
+            TaskResult::Query { query, result } => {
+                self.cache.queries.finish(query, result, Instant::now());
+            }
+        }
+    }
+
+    /// 链接表格中选中的链接
+    pub fn selected_link(&self) -> Option<&Link> {
+        let folder = self.state.folder_list_state()?.selected()?;
+        let link = self.state.link_table_state()?.selected()?;
+        self.data.get(folder)?.get(link)
+    }
+
+    /// 选中链接展开后的路径，绘制时使用，不访问文件系统
+    fn selected_path(&self) -> Option<PathBuf> {
+        self.selected_link()?
+            .resolve_in(self.cache.repo_root.as_deref())
+            .ok()
+    }
+
+    /// 把可见链接的路径以及选中链接的目录信息交给后台线程查询，已有结果并且没有过期的不查询
+    fn submit_queries(&mut self) {
+        let Some(dir) = self
+            .state
+            .folder_list_state()
+            .and_then(|s| s.selected())
+            .and_then(|idx| self.data.get(idx))
+        else {
+            return;
+        };
+        // 查询的是展开后的路径，无法展开的模板不查询
+        let visible = &self.cache.visible_links;
+        let mut queries: Vec<_> = dir
+            .get(visible.start..visible.end.min(dir.len()))
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|link| link.resolve_in(self.cache.repo_root.as_deref()).ok())
+            .map(|path| Query::new(path, QueryKind::Exists))
+            .collect();
+        // 目录信息只在链接模式下显示
+        if self.state.is_link()
+            && let Some(path) = self.selected_path()
+        {
+            queries.extend(
+                [QueryKind::Preview, QueryKind::GitStatus, QueryKind::Size]
+                    .map(|kind| Query::new(path.clone(), kind)),
+            );
+        }
+
+        let now = Instant::now();
+        for query in queries {
+            if !self.cache.queries.needs_query(&query, now) {
+                continue;
+            }
+            self.cache.queries.start(query.clone(), now);
+            self.workers
+                .get_or_insert_with(|| {
+                    WorkerPool::new(WorkerPool::default_size(), self.events.sender())
+                })
+                .submit(query);
+        }
+    }
 }
@@ -1,10 +1,26 @@
-use std::{io, path::PathBuf};
+use std::{
+    io,
+    ops::Range,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
-use crate::data::{dirset::LinkDirSet, link::Link};
+use ratatui::layout::Rect;
+
+use crate::{
+    app::{event::EventSource, worker::QueryCache},
+    data::{dirset::LinkDirSet, template},
+    error::Error,
+    import::Imported,
+    layer::Layers,
+    profile::DataSource,
+    protocol::Action,
+    term::ViewportMode,
+};
 
 pub struct RuntimeError {
     // Some if fails to read
-    pub read_data: Option<io::Error>,
+    pub read_data: Option<Error>,
     // Some if fails to save
     pub save: Option<io::Error>,
     // Some if fails to write link data
@@ -26,7 +42,7 @@ impl RuntimeError {
         }
     }
 
-    pub fn with_read_data(mut self, err: io::Error) -> Self {
+    pub fn with_read_data(mut self, err: Error) -> Self {
         self.read_data = Some(err);
         self
     }
@@ -44,6 +60,15 @@ impl RuntimeError {
 
 pub struct AppData {
     pub cursor: CursorCache,
+    // 上一次绘制时的区域，内联视口下区域的位置可能改变
+    pub area: Rect,
+    pub toast: Option<Toast>,
+    // 后台查询文件系统得到的结果
+    pub queries: QueryCache,
+    // 上一次绘制时可见的链接，只查询这些链接的路径
+    pub visible_links: Range<usize>,
+    // 当前目录所在的git仓库，运行期间当前目录不会改变，绘制时不再查找`.git`
+    pub repo_root: Option<PathBuf>,
 }
 
 impl Default for AppData {
@@ -53,13 +78,55 @@ impl Default for AppData {
 }
 
 impl AppData {
+    /// 区域改变时光标的缓存位置不再有效
+    pub fn sync_area(&mut self, area: Rect) {
+        if self.area != area {
+            self.area = area;
+            self.cursor.outdate();
+        }
+    }
+
     pub fn new() -> Self {
         Self {
             cursor: CursorCache::new(),
+            area: Rect::default(),
+            toast: None,
+            queries: QueryCache::new(),
+            visible_links: 0..0,
+            repo_root: template::current_git_root(),
         }
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct Toast {
+    message: String,
+    expire: Instant,
+}
+
+impl Toast {
+    pub const DEFAULT_DURATION: Duration = Duration::from_secs(2);
+
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self::with_duration(message, Self::DEFAULT_DURATION)
+    }
+
+    pub fn with_duration<S: Into<String>>(message: S, duration: Duration) -> Self {
+        Self {
+            message: message.into(),
+            expire: Instant::now() + duration,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn is_expired(&self, now: Instant) -> bool {
+        now >= self.expire
+    }
+}
+
 #[derive(Debug)]
 pub struct CursorCache {
     outdated: bool,
@@ -100,17 +167,55 @@ impl CursorCache {
 
 pub struct AppOption {
     pub save: bool,
+    pub viewport: ViewportMode,
+    pub source: Option<DataSource>,
+    // 重新加载个人数据时需要再次合并
+    pub layers: Layers,
+    // 重放时切换的配置也使用该目录中的副本
+    pub sandbox: Option<PathBuf>,
+    // 切换配置时使用的数据目录，`None`时使用默认的数据目录
+    pub data_dir: Option<PathBuf>,
+}
+
+impl Default for AppOption {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AppOption {
+    pub fn new() -> Self {
+        Self {
+            save: true,
+            viewport: ViewportMode::Fullscreen,
+            source: None,
+            layers: Layers::new(),
+            sandbox: None,
+            data_dir: None,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Config {
     pub path: Option<PathBuf>,
     pub save: bool,
+    pub viewport: ViewportMode,
+    // 启动时需要预览并合并的导入数据
+    pub import: Option<Imported>,
+    // 合并到个人数据中的只读数据
+    pub layers: Layers,
+    // 录制或者重放时替换终端事件的来源
+    pub events: Option<Box<dyn EventSource>>,
+    // 重放时数据文件所在的临时目录，见[`DataSource::sandbox`]
+    pub sandbox: Option<PathBuf>,
 }
 
 #[derive(Debug)]
 pub struct DataTransfer {
-    pub link: Option<Link>,
+    pub action: Option<Action>,
+    // 退出时使用的数据文件，在TUI中可能切换了配置
+    pub source: Option<DataSource>,
     pub config: Option<Config>,
     pub data: Option<LinkDirSet>,
 }
@@ -124,15 +229,17 @@ impl Default for DataTransfer {
 impl DataTransfer {
     pub fn new() -> Self {
         Self {
-            link: None,
+            action: None,
+            source: None,
             config: None,
             data: None,
         }
     }
 
-    pub fn with_link(link: Link) -> Self {
+    pub fn with_action(action: Action) -> Self {
         Self {
-            link: Some(link),
+            action: Some(action),
+            source: None,
             config: None,
             data: None,
         }
@@ -140,17 +247,23 @@ impl DataTransfer {
 
     pub fn with_path(path: PathBuf) -> Self {
         Self {
-            link: None,
+            action: None,
+            source: None,
             config: Some(Config {
                 path: Some(path),
                 save: true,
+                viewport: ViewportMode::Fullscreen,
+                import: None,
+                layers: Layers::new(),
+                events: None,
+                sandbox: None,
             }),
             data: None,
         }
     }
 
-    pub fn link(&self) -> Option<&Link> {
-        self.link.as_ref()
+    pub fn action(&self) -> Option<&Action> {
+        self.action.as_ref()
     }
 
     pub fn config(&self) -> Option<&Config> {
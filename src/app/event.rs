@@ -0,0 +1,162 @@
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    io,
+    sync::mpsc::{self, Receiver, Sender},
+    time::{Duration, Instant},
+};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+
+use crate::app::worker::{Query, QueryResult};
+
+pub const DEFAULT_TICK_RATE: Duration = Duration::from_millis(200);
+
+#[derive(Debug)]
+pub enum AppEvent {
+    Terminal(Event),
+    Tick,
+    /// 上一次事件之后完成的所有后台任务
+    Tasks(Vec<TaskResult>),
+}
+
+/// 后台任务完成后发送回事件循环的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskResult {
+    Query { query: Query, result: QueryResult },
+}
+
+/// 终端事件的来源，测试时可以替换为预先写好的事件序列
+pub trait EventSource: Debug {
+    /// 最多等待`timeout`，超时返回`None`
+    fn poll_event(&mut self, timeout: Duration) -> io::Result<Option<Event>>;
+}
+
+impl<S: EventSource + ?Sized> EventSource for Box<S> {
+    fn poll_event(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        (**self).poll_event(timeout)
+    }
+}
+
+/// 从终端读取事件
+#[derive(Debug, Default)]
+pub struct CrosstermEvents;
+
+impl EventSource for CrosstermEvents {
+    fn poll_event(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        if event::poll(timeout)? {
+            Ok(Some(event::read()?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// 依次返回给定的事件，用完之后返回`UnexpectedEof`错误，避免没有退出的脚本一直运行
+#[derive(Debug, Default)]
+pub struct ScriptedEvents {
+    events: VecDeque<Event>,
+}
+
+impl ScriptedEvents {
+    pub fn new<I: IntoIterator<Item = Event>>(events: I) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+        }
+    }
+
+    /// 每个字符都作为一次按键，`\n`为回车，`\x1b`为Esc
+    pub fn keys(keys: &str) -> Self {
+        Self::new(keys.chars().map(|c| {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\x1b' => KeyCode::Esc,
+                c => KeyCode::Char(c),
+            };
+            key(code)
+        }))
+    }
+
+    pub fn push(&mut self, event: Event) {
+        self.events.push_back(event);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+impl EventSource for ScriptedEvents {
+    fn poll_event(&mut self, _timeout: Duration) -> io::Result<Option<Event>> {
+        match self.events.pop_front() {
+            Some(event) => Ok(Some(event)),
+            None => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "scripted events exhausted before quit",
+            )),
+        }
+    }
+}
+
+/// 没有修饰键的按键事件
+pub fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+#[derive(Debug)]
+pub struct EventLoop {
+    tick_rate: Duration,
+    last_tick: Instant,
+    sender: Sender<TaskResult>,
+    receiver: Receiver<TaskResult>,
+    source: Box<dyn EventSource>,
+}
+
+impl Default for EventLoop {
+    fn default() -> Self {
+        Self::new(DEFAULT_TICK_RATE)
+    }
+}
+
+impl EventLoop {
+    pub fn new(tick_rate: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            tick_rate,
+            last_tick: Instant::now(),
+            sender,
+            receiver,
+            source: Box::new(CrosstermEvents),
+        }
+    }
+
+    pub fn with_source<S: EventSource + 'static>(mut self, source: S) -> Self {
+        self.source = Box::new(source);
+        self
+    }
+
+    pub fn tick_rate(&self) -> Duration {
+        self.tick_rate
+    }
+
+    /// 后台任务通过该发送端把结果交给事件循环
+    pub fn sender(&self) -> Sender<TaskResult> {
+        self.sender.clone()
+    }
+
+    /// 优先返回已完成的后台任务，其次是终端事件，超过`tick_rate`没有事件时返回[`AppEvent::Tick`]
+    ///
+    /// 已完成的任务一次全部取出，大量结果同时到达时只需要重新绘制一次
+    pub fn next_event(&mut self) -> io::Result<AppEvent> {
+        let results: Vec<_> = self.receiver.try_iter().collect();
+        if !results.is_empty() {
+            return Ok(AppEvent::Tasks(results));
+        }
+        let timeout = self.tick_rate.saturating_sub(self.last_tick.elapsed());
+        if let Some(event) = self.source.poll_event(timeout)? {
+            return Ok(AppEvent::Terminal(event));
+        }
+        self.last_tick = Instant::now();
+        Ok(AppEvent::Tick)
+    }
+}
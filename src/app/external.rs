@@ -0,0 +1,170 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+};
+
+use ratatui::{Terminal, prelude::Backend};
+
+use crate::{
+    app::{
+        App,
+        data::RuntimeError,
+        float::{Float, warning::BulkEditErrorState},
+    },
+    data::bulk,
+    error::{self, Context, Error},
+    get_data,
+    temp::PrivateDir,
+    term, try_save,
+};
+
+/// 需要暂时离开TUI才能完成的操作，由事件循环在两次绘制之间执行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternalAction {
+    Suspend,
+    EditPath(PathBuf),
+    EditData,
+    /// `text`为`None`时从文件夹中的链接生成，否则继续编辑上一次出错的文本
+    BulkEdit {
+        folder: String,
+        text: Option<String>,
+    },
+}
+
+/// 依次使用`$VISUAL`、`$EDITOR`，都没有设置时使用`vi`
+pub fn editor_command() -> Command {
+    let editor = ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(env::var_os)
+        .find(|editor| !editor.is_empty())
+        .unwrap_or_else(|| OsString::from("vi"));
+
+    // 允许`code -w`这样带参数的写法
+    match editor.to_str() {
+        Some(editor) => {
+            let mut parts = editor.split_whitespace();
+            let mut command = Command::new(parts.next().unwrap_or("vi"));
+            command.args(parts);
+            command
+        }
+        None => Command::new(editor),
+    }
+}
+
+pub fn open_in_editor(path: &Path) -> io::Result<ExitStatus> {
+    let mut command = editor_command();
+    if path.is_dir() {
+        command.current_dir(path);
+    }
+    command.arg(path).status()
+}
+
+pub fn run_external<B: Backend>(
+    app: &mut App,
+    terminal: &mut Terminal<B>,
+    action: ExternalAction,
+) -> io::Result<()> {
+    tracing::debug!(?action, "run external");
+    let viewport = app.option.viewport;
+    term::suspend(viewport, terminal)?;
+    let result = match &action {
+        ExternalAction::Suspend => {
+            term::raise_suspend();
+            Ok(())
+        }
+        ExternalAction::EditPath(path) => open_in_editor(path)
+            .map(|_| ())
+            .context("failed to open editor"),
+        ExternalAction::EditData => edit_data(app),
+        ExternalAction::BulkEdit { folder, text } => bulk_edit(app, folder, text.as_deref()),
+    };
+    term::resume(viewport, terminal)?;
+    app.cache.cursor.outdate();
+
+    if let Err(err) = result {
+        app.add_float(Float::Warning(err.into()));
+    }
+    Ok(())
+}
+
+/// 编辑前先保存，编辑后重新加载数据文件
+///
+/// 如果编辑后的数据无法解析，保留内存中的数据并显示数据损坏的警告
+fn edit_data(app: &mut App) -> error::Result<()> {
+    let Some(data_path) = app.option.source.as_ref().map(|s| s.path().to_path_buf()) else {
+        return Ok(());
+    };
+    try_save(app.option.save, &data_path, &mut app.data)?;
+    let status = open_in_editor(&data_path).context("failed to open editor")?;
+    // 编辑器以非零状态退出时（如vim的`:cq`）不重新加载
+    if !status.success() {
+        app.toast("Edit cancelled");
+        return Ok(());
+    }
+
+    match get_data(&data_path) {
+        Ok(data) => {
+            app.reload_data(data);
+            app.option.save = true;
+        }
+        Err(err) => {
+            let mut runtime = RuntimeError::new().with_read_data(err);
+            app.check_corrept_data(&mut runtime);
+        }
+    }
+    Ok(())
+}
+
+/// 类似vidir，把文件夹中的链接写入临时文件，编辑后把差异应用回文件夹
+fn bulk_edit(app: &mut App, folder: &str, text: Option<&str>) -> error::Result<()> {
+    let Some(idx) = app.data.index_of(folder) else {
+        return Err(Error::not_found("folder", folder));
+    };
+
+    // 文件中是所有的链接，只允许当前用户读写
+    let dir = PrivateDir::new("bulk").context("failed to create a temporary directory")?;
+    let text = match text {
+        Some(text) => text.to_string(),
+        None => bulk::dump(&app.data[idx]),
+    };
+    let path = dir
+        .create_file("bulk.txt")
+        .and_then(|(mut file, path)| file.write_all(text.as_bytes()).map(|()| path))
+        .context("failed to write the bulk edit file")?;
+    let result = open_in_editor(&path).and_then(|status| {
+        // 编辑器以非零状态退出时（如vim的`:cq`）放弃这次编辑
+        if status.success() {
+            fs::read_to_string(&path).map(Some)
+        } else {
+            Ok(None)
+        }
+    });
+    drop(dir);
+    let Some(text) = result.context("failed to open editor")? else {
+        app.toast("Bulk edit cancelled");
+        return Ok(());
+    };
+
+    match bulk::apply(&mut app.data.dir_mut(idx), &text) {
+        Ok(summary) => {
+            // 链接变少时选中的位置可能已经超出范围
+            let len = app.data[idx].len();
+            if let Some(state) = app.state.link_table_state_mut()
+                && let Some(selected) = state.selected()
+            {
+                state.select(Some(selected.min(len.saturating_sub(1))));
+            }
+            app.toast(summary.to_string());
+        }
+        Err(err) => app.add_float(Float::BulkEditError(BulkEditErrorState::new(
+            folder.to_string(),
+            text,
+            err.to_string(),
+        ))),
+    }
+    Ok(())
+}
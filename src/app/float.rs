@@ -1,48 +1,64 @@
 use std::{array, iter::Flatten};
 
-use crate::{
-    app::{
-        float::{
-            confirm::{
-                ConfirmChoice, FolderDeleteConfirmState, FolderSaveConfirmState,
-                LinkDeleteConfirmState, LinkSaveConfirmState,
-            },
-            edit::{FolderEditState, LinkEditState},
-            help::HelpState,
-            warning::{CorruptDataWarningState, WarningState},
-        },
-        normal::{FolderNormalState, LinkNormalState},
-    },
-    data::{dir::LinkDir, dirset::LinkDirSet},
+use crate::app::float::{
+    command::CommandState,
+    confirm::{ConfirmState, FolderSaveConfirmState, LinkSaveConfirmState},
+    edit::{FolderEditState, LinkEditState},
+    help::HelpState,
+    import::ImportPreviewState,
+    profile::ProfileState,
+    warning::{BulkEditErrorState, CorruptDataWarningState, WarningState},
 };
 
+pub mod command;
 pub mod common;
 pub mod confirm;
 pub mod edit;
 pub mod help;
+pub mod import;
+pub mod profile;
 pub mod warning;
 
 pub use common::*;
 
-pub type FolderDeleteConfirmCallbackType =
-    Box<dyn FnOnce(ConfirmChoice, &mut FolderNormalState, &mut LinkDirSet)>;
-pub type LinkDeleteConfirmCallbackType =
-    Box<dyn FnOnce(ConfirmChoice, &mut LinkNormalState, &mut LinkDir)>;
 pub type FolderSaveConfirmCallbackType = Box<dyn FnOnce()>;
 
 #[derive(Debug)]
 pub enum Float {
     LinkEdit(LinkEditState),
     FolderEdit(FolderEditState),
-    FolderDeleteConfirm(FolderDeleteConfirmState<FolderDeleteConfirmCallbackType>),
-    LinkDeleteConfirm(LinkDeleteConfirmState<LinkDeleteConfirmCallbackType>),
+    Confirm(ConfirmState),
     Warning(WarningState),
     FolderSaveConfirm(FolderSaveConfirmState),
     LinkSaveConfirm(LinkSaveConfirmState),
     CorruptDataWarning(CorruptDataWarningState),
+    BulkEditError(BulkEditErrorState),
+    ImportPreview(ImportPreviewState),
+    Command(CommandState),
+    Profile(ProfileState),
     Help(HelpState),
 }
 
+impl Float {
+    /// 用于日志的浮窗名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Float::LinkEdit(_) => "link-edit",
+            Float::FolderEdit(_) => "folder-edit",
+            Float::Confirm(_) => "confirm",
+            Float::Warning(_) => "warning",
+            Float::FolderSaveConfirm(_) => "folder-save-confirm",
+            Float::LinkSaveConfirm(_) => "link-save-confirm",
+            Float::CorruptDataWarning(_) => "corrupt-data-warning",
+            Float::BulkEditError(_) => "bulk-edit-error",
+            Float::ImportPreview(_) => "import-preview",
+            Float::Command(_) => "command",
+            Float::Profile(_) => "profile",
+            Float::Help(_) => "help",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct FloatActionResult {
     pub primary: Option<Float>,
@@ -0,0 +1,41 @@
+use std::path::{Path, PathBuf};
+
+use tui_input::Input;
+
+use crate::app::{float::FloatState, message::EditMessage};
+
+/// 输入在链接目录中执行的命令，确认后退出并交给shell执行
+#[derive(Debug)]
+pub struct CommandState {
+    dir: PathBuf,
+    input: Input,
+}
+
+impl FloatState for CommandState {
+    type Message = EditMessage;
+}
+
+impl CommandState {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            input: Input::default(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn value(&self) -> &str {
+        self.input.value()
+    }
+
+    pub fn input_mut(&mut self) -> &mut Input {
+        &mut self.input
+    }
+
+    pub fn into_dir(self) -> PathBuf {
+        self.dir
+    }
+}
@@ -1,18 +1,13 @@
-use core::fmt;
-use std::fmt::{Debug, Formatter};
-
-use crate::{
-    app::{
-        App,
-        float::{
-            Float, FloatState,
-            edit::{FolderEditState, LinkEditState},
-        },
-        message::ConfirmMessage,
-        normal::{FolderNormalState, LinkNormalState},
-        state::{AppState, NormalState},
+use crate::app::{
+    App,
+    float::{
+        Float, FloatState,
+        edit::{FolderEditState, LinkEditState},
     },
-    data::{dir::LinkDir, dirset::LinkDirSet},
+    message::ConfirmMessage,
+    normal::{FolderNormalState, LinkNormalState},
+    operation::PendingOperation,
+    state::{AppState, NormalState},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -30,43 +25,22 @@ pub enum ConfirmCancelChoice {
     Cancel,
 }
 
-// trait FolderDeleteConfirmCallback = FnOnce(ConfirmChoice, &mut FolderNormalState, &mut LinkDirSet);
-
-pub struct FolderDeleteConfirmState<F>
-where
-    F: FnOnce(ConfirmChoice, &mut FolderNormalState, &mut LinkDirSet),
-{
+/// 确认后通过`App::apply`执行`operation`
+#[derive(Debug)]
+pub struct ConfirmState {
     choice: ConfirmChoice,
-    callback: F,
-}
-
-impl<F> Debug for FolderDeleteConfirmState<F>
-where
-    F: FnOnce(ConfirmChoice, &mut FolderNormalState, &mut LinkDirSet),
-{
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.debug_struct("FolderDeleteConfirmState")
-            .field("choice", &self.choice)
-            .field("callback", &"FnOnce(ConfirmChoice)")
-            .finish()
-    }
+    operation: PendingOperation,
 }
 
-impl<F> FloatState for FolderDeleteConfirmState<F>
-where
-    F: FnOnce(ConfirmChoice, &mut FolderNormalState, &mut LinkDirSet),
-{
+impl FloatState for ConfirmState {
     type Message = ConfirmMessage;
 }
 
-impl<F> FolderDeleteConfirmState<F>
-where
-    F: FnOnce(ConfirmChoice, &mut FolderNormalState, &mut LinkDirSet),
-{
-    pub fn new(callback: F) -> Self {
+impl ConfirmState {
+    pub fn new(operation: PendingOperation) -> Self {
         Self {
             choice: ConfirmChoice::No,
-            callback,
+            operation,
         }
     }
 
@@ -79,75 +53,12 @@ where
         self.choice
     }
 
-    pub fn call(self, state: &mut FolderNormalState, data: &mut LinkDirSet) {
-        let function = self.callback;
-        function(self.choice, state, data);
-    }
-
-    pub fn switch_chioce(&mut self) {
-        self.choice = match self.choice {
-            ConfirmChoice::Yes => ConfirmChoice::No,
-            ConfirmChoice::No => ConfirmChoice::Yes,
-        }
-    }
-
-    pub fn change_choice(&mut self, choice: ConfirmChoice) {
-        self.choice = choice;
-    }
-}
-
-pub struct LinkDeleteConfirmState<F>
-where
-    F: FnOnce(ConfirmChoice, &mut LinkNormalState, &mut LinkDir),
-{
-    choice: ConfirmChoice,
-    callback: F,
-    dir_idx: usize,
-}
-
-impl<F> Debug for LinkDeleteConfirmState<F>
-where
-    F: FnOnce(ConfirmChoice, &mut LinkNormalState, &mut LinkDir),
-{
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.debug_struct("LinkDeleteConfirmState")
-            .field("choice", &self.choice)
-            .field("callback", &"FnOnce(ConfirmChoice)")
-            .finish()
-    }
-}
-
-impl<F> FloatState for LinkDeleteConfirmState<F>
-where
-    F: FnOnce(ConfirmChoice, &mut LinkNormalState, &mut LinkDir),
-{
-    type Message = ConfirmMessage;
-}
-
-impl<F> LinkDeleteConfirmState<F>
-where
-    F: FnOnce(ConfirmChoice, &mut LinkNormalState, &mut LinkDir),
-{
-    pub fn new(callback: F, dir_idx: usize) -> Self {
-        Self {
-            choice: ConfirmChoice::No,
-            callback,
-            dir_idx,
-        }
+    pub fn operation(&self) -> &PendingOperation {
+        &self.operation
     }
 
-    pub fn with_choice(mut self, choice: ConfirmChoice) -> Self {
-        self.choice = choice;
-        self
-    }
-
-    pub fn choice(&self) -> ConfirmChoice {
-        self.choice
-    }
-
-    pub fn call(self, state: &mut LinkNormalState, data: &mut LinkDir) {
-        let function = self.callback;
-        function(self.choice, state, data);
+    pub fn into_operation(self) -> PendingOperation {
+        self.operation
     }
 
     pub fn switch_chioce(&mut self) {
@@ -160,10 +71,6 @@ where
     pub fn change_choice(&mut self, choice: ConfirmChoice) {
         self.choice = choice;
     }
-
-    pub fn dir_idx(&self) -> usize {
-        self.dir_idx
-    }
 }
 
 #[derive(Debug)]
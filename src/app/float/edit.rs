@@ -2,10 +2,13 @@ use std::ffi::OsStr;
 
 use tui_input::Input;
 
-use crate::app::{
-    float::FloatState,
-    message::EditMessage,
-    normal::{InputMode, InputPart},
+use crate::{
+    app::{
+        float::FloatState,
+        message::EditMessage,
+        normal::{InputMode, InputPart},
+    },
+    data::escape,
 };
 
 #[derive(Debug)]
@@ -142,7 +145,7 @@ impl LinkEditState {
 
     pub fn with_value(mut self, key: &str, value: &OsStr) -> Self {
         let key_input = Input::new(key.to_string());
-        let value_input = Input::new(value.to_string_lossy().to_string());
+        let value_input = Input::new(escape::escape(value));
         self.input = (key_input, value_input);
         self
     }
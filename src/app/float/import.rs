@@ -0,0 +1,93 @@
+use ratatui::widgets::TableState;
+
+use crate::{
+    app::{float::FloatState, message::ChooseMessage},
+    import::ImportItem,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImportPreviewChoice {
+    #[default]
+    Import,
+    Cancel,
+}
+
+/// 导入的链接在合并之前先预览，确认后才会写入数据
+#[derive(Debug)]
+pub struct ImportPreviewState {
+    choice: ImportPreviewChoice,
+    items: Vec<ImportItem>,
+    // 无法转换为链接的书签数量
+    skipped: usize,
+    table: TableState,
+}
+
+impl FloatState for ImportPreviewState {
+    type Message = ChooseMessage<bool>;
+}
+
+impl ImportPreviewState {
+    pub fn new(items: Vec<ImportItem>) -> Self {
+        let table = TableState::default().with_selected((!items.is_empty()).then_some(0));
+        Self {
+            choice: ImportPreviewChoice::Import,
+            items,
+            skipped: 0,
+            table,
+        }
+    }
+
+    pub fn with_skipped(mut self, skipped: usize) -> Self {
+        self.skipped = skipped;
+        self
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    pub fn choice(&self) -> ImportPreviewChoice {
+        self.choice
+    }
+
+    pub fn items(&self) -> &[ImportItem] {
+        &self.items
+    }
+
+    pub fn into_items(self) -> Vec<ImportItem> {
+        self.items
+    }
+
+    pub fn table_state_mut(&mut self) -> &mut TableState {
+        &mut self.table
+    }
+
+    pub fn switch_left(&mut self) {
+        self.choice = ImportPreviewChoice::Import;
+    }
+
+    pub fn switch_right(&mut self) {
+        self.choice = ImportPreviewChoice::Cancel;
+    }
+
+    pub fn switch(&mut self) {
+        self.choice = match self.choice {
+            ImportPreviewChoice::Import => ImportPreviewChoice::Cancel,
+            ImportPreviewChoice::Cancel => ImportPreviewChoice::Import,
+        }
+    }
+
+    pub fn switch_up(&mut self) {
+        self.table.select_previous();
+    }
+
+    pub fn switch_down(&mut self) {
+        if self
+            .table
+            .selected()
+            .is_some_and(|idx| idx + 1 < self.items.len())
+        {
+            self.table.select_next();
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use ratatui::widgets::ListState;
+
+use crate::app::{float::FloatState, message::ChooseMessage};
+
+/// 选择要切换到的配置
+#[derive(Debug)]
+pub struct ProfileState {
+    profiles: Vec<String>,
+    current: Option<String>,
+    list: ListState,
+}
+
+impl FloatState for ProfileState {
+    type Message = ChooseMessage<bool>;
+}
+
+impl ProfileState {
+    pub fn new(profiles: Vec<String>) -> Self {
+        Self {
+            profiles,
+            current: None,
+            list: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    /// 标出当前使用的配置并选中它
+    pub fn set_current(&mut self, current: Option<&str>) {
+        self.current = current.map(String::from);
+        if let Some(idx) =
+            current.and_then(|current| self.profiles.iter().position(|p| p == current))
+        {
+            self.list.select(Some(idx));
+        }
+    }
+
+    pub fn profiles(&self) -> &[String] {
+        &self.profiles
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.list
+            .selected()
+            .and_then(|idx| self.profiles.get(idx))
+            .map(String::as_str)
+    }
+
+    pub fn list_state_mut(&mut self) -> &mut ListState {
+        &mut self.list
+    }
+
+    pub fn switch_up(&mut self) {
+        self.list.select_previous();
+    }
+
+    pub fn switch_down(&mut self) {
+        if self
+            .list
+            .selected()
+            .is_some_and(|idx| idx + 1 < self.profiles.len())
+        {
+            self.list.select_next();
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use crate::app::{
-    float::FloatState,
-    message::{ChooseMessage, WarningMessage},
+use crate::{
+    app::{
+        float::FloatState,
+        message::{ChooseMessage, WarningMessage},
+    },
+    error::Error,
 };
 
 #[derive(Debug, Clone)]
@@ -30,6 +33,19 @@ impl WarningState {
     }
 }
 
+/// 错误信息的首字母大写后显示
+impl From<Error> for WarningState {
+    fn from(err: Error) -> Self {
+        let message = err.to_string();
+        let mut chars = message.chars();
+        let message = match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => message,
+        };
+        Self::new(message)
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum CorruptDataWarningChoice {
     #[default]
@@ -90,3 +106,65 @@ impl CorruptDataWarningState {
 
     pub fn switch_down(&mut self) {}
 }
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BulkEditErrorChoice {
+    #[default]
+    Edit,
+    Discard,
+}
+
+/// 批量编辑的结果无法应用时显示，可以带着出错的文本重新编辑
+#[derive(Debug)]
+pub struct BulkEditErrorState {
+    choice: BulkEditErrorChoice,
+    folder: String,
+    text: String,
+    message: String,
+}
+
+impl FloatState for BulkEditErrorState {
+    type Message = ChooseMessage<bool>;
+}
+
+impl BulkEditErrorState {
+    pub fn new(folder: String, text: String, message: String) -> Self {
+        Self {
+            choice: BulkEditErrorChoice::Edit,
+            folder,
+            text,
+            message,
+        }
+    }
+
+    pub fn choice(&self) -> BulkEditErrorChoice {
+        self.choice
+    }
+
+    pub fn folder(&self) -> &str {
+        &self.folder
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn into_text(self) -> (String, String) {
+        (self.folder, self.text)
+    }
+
+    pub fn switch_left(&mut self) {
+        self.choice = BulkEditErrorChoice::Edit;
+    }
+
+    pub fn switch_right(&mut self) {
+        self.choice = BulkEditErrorChoice::Discard;
+    }
+
+    pub fn switch(&mut self) {
+        self.choice = match self.choice {
+            BulkEditErrorChoice::Edit => BulkEditErrorChoice::Discard,
+            BulkEditErrorChoice::Discard => BulkEditErrorChoice::Edit,
+        }
+    }
+}
@@ -9,25 +9,57 @@ use crate::{
     app::message::{EditMessage, MessageUpdater, NormalFolderMessage, NormalLinkMessage},
     app::{
         App,
-        float::Float,
+        external::ExternalAction,
+        float::{Float, FloatActionResult},
         normal::{FolderNormalState, LinkNormalState},
         state::{AppState, NormalState},
     },
-    data::{dir::LinkDir, dirset::LinkDirSet},
+    data::dirset::{LinkDirMut, LinkDirSet},
+    protocol::Action,
 };
 
 pub fn handle_key_event(app: &mut App, key: KeyEvent) {
+    // Ctrl+Z在任何界面下都会挂起程序
+    if key.kind == KeyEventKind::Press
+        && key.modifiers == KeyModifiers::CONTROL
+        && matches!(key.code, KeyCode::Char('z') | KeyCode::Char('Z'))
+    {
+        app.set_external(ExternalAction::Suspend);
+        return;
+    }
     match app.get_float() {
         None => handle_key_event_basic(app, key),
         Some(float) => handle_key_event_float(app, key, float),
     }
 }
 
+/// 粘贴的内容只交给编辑框中当前的输入框，其余情况忽略
+pub fn handle_paste_event(app: &mut App, text: &str) {
+    let Some(float) = app.get_float() else {
+        return;
+    };
+    let float = match float {
+        Float::FolderEdit(state) => {
+            Float::FolderEdit(edit::handle_edit_folder_paste(app, text, state))
+        }
+        Float::LinkEdit(state) => Float::LinkEdit(edit::handle_edit_link_paste(app, text, state)),
+        Float::Command(mut state) => {
+            let text = text.lines().next().unwrap_or_default();
+            edit::input_paste(state.input_mut(), text, &mut app.cache.cursor);
+            Float::Command(state)
+        }
+        float => float,
+    };
+    // 放回原来的浮窗，不是新的浮窗
+    app.float.push(float);
+}
+
 pub fn handle_key_event_basic(app: &mut App, key: KeyEvent) {
     let mut opt_mod = None;
     let data = &mut app.data;
     let state = &mut app.state;
     let mut float = None;
+    let mut external = None;
 
     match state {
         AppState::Normal(part) => match &mut **part {
@@ -35,15 +67,25 @@ pub fn handle_key_event_basic(app: &mut App, key: KeyEvent) {
                 let mut opt_msg = handle_normal_folder_key_event(key);
                 while let Some(msg) = opt_msg {
                     let updater = handle_normal_folder_message(state, data, msg);
-                    (opt_msg, opt_mod, float) = (updater.message, updater.state, updater.float);
+                    (opt_msg, opt_mod, float, external) = (
+                        updater.message,
+                        updater.state,
+                        updater.float,
+                        updater.external,
+                    );
                 }
             }
             NormalState::Link(state) => {
                 let mut opt_msg = handle_normal_link_key_event(key);
                 while let Some(msg) = opt_msg {
                     let idx = state.folder_list_state().selected().unwrap();
-                    let updater = handle_normal_link_message(state, &mut data[idx], msg);
-                    (opt_msg, opt_mod, float) = (updater.message, updater.state, updater.float);
+                    let updater = handle_normal_link_message(state, &mut data.dir_mut(idx), msg);
+                    (opt_msg, opt_mod, float, external) = (
+                        updater.message,
+                        updater.state,
+                        updater.float,
+                        updater.external,
+                    );
                 }
             }
         },
@@ -54,26 +96,43 @@ pub fn handle_key_event_basic(app: &mut App, key: KeyEvent) {
     if let Some(mod_change) = opt_mod {
         app.set_state(mod_change);
     }
-    if let Some(f) = float {
+    if let Some(mut f) = float {
+        if let Float::Profile(state) = &mut f {
+            let current = app.option.source.as_ref().and_then(|s| s.profile_name());
+            state.set_current(current);
+        }
         app.add_float(f);
     }
+    if let Some(action) = external {
+        app.set_external(action);
+    }
 }
 
 pub fn handle_key_event_float(app: &mut App, key: KeyEvent, float: Float) {
-    let float_action = match float {
+    let name = float.name();
+    let FloatActionResult { primary, new } = match float {
         Float::FolderEdit(state) => edit::handle_edit_folder_key(app, key, state),
         Float::LinkEdit(state) => edit::handle_edit_link_key(app, key, state),
-        Float::FolderDeleteConfirm(state) => {
-            float::handle_folder_delete_confirm_key(app, key, state)
-        }
-        Float::LinkDeleteConfirm(state) => float::handle_link_delete_confirm_key(app, key, state),
+        Float::Confirm(state) => float::handle_confirm_key(app, key, state),
         Float::Warning(state) => float::handle_warning_key(app, key, state),
         Float::FolderSaveConfirm(state) => float::handle_folder_save_confirm_key(app, key, state),
         Float::LinkSaveConfirm(state) => float::handle_link_save_confirm_key(app, key, state),
         Float::CorruptDataWarning(state) => float::handle_corrupt_data_warning_key(app, key, state),
+        Float::BulkEditError(state) => float::handle_bulk_edit_error_key(app, key, state),
+        Float::ImportPreview(state) => float::handle_import_preview_key(app, key, state),
+        Float::Command(state) => edit::handle_command_key(app, key, state),
+        Float::Profile(state) => float::handle_profile_key(app, key, state),
         Float::Help(state) => float::handle_help_key(app, key, state),
     };
-    app.extend_float(float_action);
+    match primary {
+        // 同一个浮窗仍然保留时不记录日志
+        Some(float) if float.name() == name => app.float.push(float),
+        primary => {
+            tracing::debug!(float = name, depth = app.float.len(), "pop float");
+            app.extend_float(primary);
+        }
+    }
+    app.extend_float(new);
 }
 
 pub fn handle_normal_folder_key_event(key: KeyEvent) -> Option<NormalFolderMessage> {
@@ -94,6 +153,10 @@ pub fn handle_normal_folder_key_event(key: KeyEvent) -> Option<NormalFolderMessa
                 KeyCode::Char('a') => Some(NormalFolderMessage::Append),
                 KeyCode::Char('r') => Some(NormalFolderMessage::Rename),
                 KeyCode::Char('x') => Some(NormalFolderMessage::Remove),
+                KeyCode::Char('b') => Some(NormalFolderMessage::BulkEdit),
+                KeyCode::Char('f') => Some(NormalFolderMessage::Fork),
+                KeyCode::Char('E') => Some(NormalFolderMessage::EditData),
+                KeyCode::Char('P') => Some(NormalFolderMessage::Profile),
                 KeyCode::Char('?') => Some(NormalFolderMessage::Help),
                 _ => None,
             },
@@ -122,6 +185,15 @@ pub fn handle_normal_link_key_event(key: KeyEvent) -> Option<NormalLinkMessage>
                 KeyCode::Char('a') => Some(NormalLinkMessage::Append),
                 KeyCode::Char('r') => Some(NormalLinkMessage::Rename),
                 KeyCode::Char('x') => Some(NormalLinkMessage::Remove),
+                KeyCode::Char('e') => Some(NormalLinkMessage::OpenEditor),
+                KeyCode::Char('p') => Some(NormalLinkMessage::Pushd),
+                KeyCode::Char('y') => Some(NormalLinkMessage::PrintPath),
+                KeyCode::Char('o') => Some(NormalLinkMessage::EditHere),
+                KeyCode::Char('!') => Some(NormalLinkMessage::RunCommand),
+                KeyCode::Char('b') => Some(NormalLinkMessage::BulkEdit),
+                KeyCode::Char('f') => Some(NormalLinkMessage::Fork),
+                KeyCode::Char('E') => Some(NormalLinkMessage::EditData),
+                KeyCode::Char('P') => Some(NormalLinkMessage::Profile),
                 KeyCode::Char('?') => Some(NormalLinkMessage::Help),
                 _ => None,
             },
@@ -153,6 +225,10 @@ pub fn handle_normal_folder_message(
         NormalFolderMessage::Append => normal::folder_append(state, data),
         NormalFolderMessage::Rename => normal::folder_rename(state, data),
         NormalFolderMessage::Remove => normal::folder_remove(state, data),
+        NormalFolderMessage::BulkEdit => normal::folder_bulk_edit(state, data),
+        NormalFolderMessage::Fork => normal::folder_fork(state, data),
+        NormalFolderMessage::EditData => normal::folder_edit_data(),
+        NormalFolderMessage::Profile => normal::choose_profile(),
         NormalFolderMessage::Quit => normal::folder_quit(),
         NormalFolderMessage::Item(idx) => normal::folder_item(state, idx),
         NormalFolderMessage::ToDir(idx) => normal::folder_to_dir(state, data, idx),
@@ -162,7 +238,7 @@ pub fn handle_normal_folder_message(
 
 pub fn handle_normal_link_message(
     state: &mut LinkNormalState,
-    data: &mut LinkDir,
+    data: &mut LinkDirMut,
     message: NormalLinkMessage,
 ) -> MessageUpdater<NormalLinkMessage> {
     match message {
@@ -175,6 +251,15 @@ pub fn handle_normal_link_message(
         NormalLinkMessage::Append => normal::link_append(state, data),
         NormalLinkMessage::Rename => normal::link_rename(state, data),
         NormalLinkMessage::Remove => normal::link_remove(state, data),
+        NormalLinkMessage::OpenEditor => normal::link_open_editor(state, data),
+        NormalLinkMessage::Pushd => normal::link_finish(state, data, Action::Pushd),
+        NormalLinkMessage::PrintPath => normal::link_finish(state, data, Action::Print),
+        NormalLinkMessage::EditHere => normal::link_finish(state, data, Action::OpenEditor),
+        NormalLinkMessage::RunCommand => normal::link_run_command(state, data),
+        NormalLinkMessage::BulkEdit => normal::link_bulk_edit(state, data),
+        NormalLinkMessage::Fork => normal::link_fork(state, data),
+        NormalLinkMessage::EditData => normal::link_edit_data(),
+        NormalLinkMessage::Profile => normal::choose_profile(),
         NormalLinkMessage::Quit => normal::link_quit(),
         NormalLinkMessage::Item(idx) => normal::link_item(state, idx),
         NormalLinkMessage::ToLink(idx) => normal::link_to_link(state, data, idx),
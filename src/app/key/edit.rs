@@ -1,18 +1,18 @@
 use std::path::PathBuf;
 
 use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
-use tui_input::{Input, backend::crossterm::EventHandler};
+use tui_input::{Input, InputRequest, backend::crossterm::EventHandler};
 
 use crate::{
     App,
     app::{
-        data::CursorCache,
+        data::{CursorCache, DataTransfer},
         float::{
             Float, FloatActionResult,
+            command::CommandState,
             confirm::{FolderSaveConfirmState, LinkSaveConfirmState},
             edit::{FolderEditState, LinkEditState},
             help::{HelpEntry, HelpState},
-            warning::WarningState,
         },
         key::common,
         message::{EditMessage, FloatUpdater},
@@ -21,8 +21,11 @@ use crate::{
     },
     data::{
         dir::LinkDir,
+        escape,
         link::{self, Link},
     },
+    error::Error,
+    protocol::Action,
 };
 
 #[inline]
@@ -31,6 +34,81 @@ pub fn input_handle_key(input: &mut Input, event: &Event, cursor_cache: &mut Cur
     cursor_cache.outdate();
 }
 
+pub fn input_paste(input: &mut Input, text: &str, cursor_cache: &mut CursorCache) {
+    for c in text.chars() {
+        input.handle(InputRequest::InsertChar(c));
+    }
+    cursor_cache.outdate();
+}
+
+pub fn handle_edit_folder_paste(
+    app: &mut App,
+    text: &str,
+    mut state: FolderEditState,
+) -> FolderEditState {
+    if *state.mode() == InputMode::Normal {
+        state.switch_mode();
+    }
+    let text = text.lines().next().unwrap_or_default();
+    input_paste(state.input_mut(), text, &mut app.cache.cursor);
+    state
+}
+
+#[inline]
+pub fn handle_command_key(app: &mut App, key: KeyEvent, state: CommandState) -> FloatActionResult {
+    common::handle_common_key(
+        app,
+        key,
+        state,
+        command_key,
+        command_message,
+        Float::Command,
+    )
+}
+
+pub fn command_key(key: KeyEvent) -> Option<EditMessage> {
+    if key.kind != KeyEventKind::Press {
+        return None;
+    }
+    match (key.modifiers, key.code) {
+        (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) | (_, KeyCode::Esc) => {
+            Some(EditMessage::Quit(None, false))
+        }
+        (_, KeyCode::Enter) => Some(EditMessage::Confirm),
+        _ => Some(EditMessage::HandleInput(key)),
+    }
+}
+
+pub fn command_message(
+    app: &mut App,
+    mut state: CommandState,
+    msg: EditMessage,
+) -> FloatUpdater<CommandState> {
+    match msg {
+        EditMessage::HandleInput(key_event) => {
+            input_handle_key(
+                state.input_mut(),
+                &Event::Key(key_event),
+                &mut app.cache.cursor,
+            );
+            FloatUpdater::new().with_state(state)
+        }
+        EditMessage::Confirm if state.value().trim().is_empty() => {
+            FloatUpdater::new().with_state(state)
+        }
+        EditMessage::Confirm => {
+            let command = state.value().to_string();
+            let dir = state.into_dir();
+            app.set_state(AppState::Quit(Box::new(DataTransfer::with_action(
+                Action::ExecCommand { dir, command },
+            ))));
+            FloatUpdater::new()
+        }
+        EditMessage::Quit(_, _) => FloatUpdater::new(),
+        _ => FloatUpdater::new().with_state(state),
+    }
+}
+
 #[inline]
 pub fn handle_edit_folder_key(
     app: &mut App,
@@ -168,16 +246,18 @@ pub fn folder_confirm_normal(
                 Ok(dir) => dir,
                 // TODO: handle Err later (identifier empty)
                 Err(err) => {
-                    let msg = err.message().to_owned();
-                    return FloatUpdater::new().with_float(Float::Warning(WarningState::new(msg)));
+                    return FloatUpdater::new().with_float(Float::Warning(Error::from(err).into()));
                 }
             };
             // TODO: handle Err later (identifier already exists)
             match data.push(dir) {
-                Ok(_) => Some(data.len().saturating_sub(1)),
+                Ok(_) => {
+                    let select = Some(data.len().saturating_sub(1));
+                    app.toast("Folder added");
+                    select
+                }
                 Err(err) => {
-                    let msg = err.message().to_owned();
-                    return FloatUpdater::new().with_float(Float::Warning(WarningState::new(msg)));
+                    return FloatUpdater::new().with_float(Float::Warning(Error::from(err).into()));
                 }
             }
         }
@@ -186,11 +266,10 @@ pub fn folder_confirm_normal(
             if name != data[idx].identifier() {
                 // TODO: handle Err later (identifier empty or already exists)
                 match data.rename(idx, name) {
-                    Ok(_) => {}
+                    Ok(_) => app.toast("Folder renamed"),
                     Err(err) => {
-                        let msg = err.message().to_owned();
                         return FloatUpdater::new()
-                            .with_float(Float::Warning(WarningState::new(msg)));
+                            .with_float(Float::Warning(Error::from(err).into()));
                     }
                 }
             }
@@ -301,6 +380,28 @@ pub fn folder_back_editing(
     FloatUpdater::new()
 }
 
+pub fn handle_edit_link_paste(
+    app: &mut App,
+    text: &str,
+    mut state: LinkEditState,
+) -> LinkEditState {
+    if *state.mode() == InputMode::Normal {
+        state.switch_mode();
+    }
+    match state.part() {
+        InputPart::Key => {
+            let text = text.lines().next().unwrap_or_default();
+            input_paste(state.key_input_mut(), text, &mut app.cache.cursor);
+        }
+        InputPart::Value => {
+            // 输入框中是转义后的路径
+            let text = escape::escape(link::clean_pasted_path(text).as_os_str());
+            input_paste(state.value_input_mut(), &text, &mut app.cache.cursor);
+        }
+    }
+    state
+}
+
 #[inline]
 pub fn handle_edit_link_key(
     app: &mut App,
@@ -452,7 +553,7 @@ pub fn link_confirm_normal(
 ) -> FloatUpdater<LinkEditState> {
     let (key, value) = state.value();
     let value: PathBuf = link::get_vaild_path(value).unwrap_or_default();
-    let data = &mut app.data[state.from()];
+    let mut data = app.data.dir_mut(state.from());
 
     let select = match state.selected() {
         None => {
@@ -462,33 +563,37 @@ pub fn link_confirm_normal(
                 Ok(link) => link,
                 // TODO: handle Err later (identifier or path empty)
                 Err(err) => {
-                    let msg = err.message().to_owned();
-                    return FloatUpdater::new().with_float(Float::Warning(WarningState::new(msg)));
+                    return FloatUpdater::new().with_float(Float::Warning(Error::from(err).into()));
                 }
             };
             // TODO: handle Err later (identifier already exists)
             match data.push(link) {
-                Ok(_) => Some(data.len().saturating_sub(1)),
+                Ok(_) => {
+                    let select = Some(data.len().saturating_sub(1));
+                    app.toast("Link added");
+                    select
+                }
                 Err(err) => {
-                    let msg = err.message().to_owned();
-                    return FloatUpdater::new().with_float(Float::Warning(WarningState::new(msg)));
+                    return FloatUpdater::new().with_float(Float::Warning(Error::from(err).into()));
                 }
             }
         }
         Some(idx) => {
             // rename
             // TODO: handle Err later (identifier or path empty, identifier already exists)
+            let changed = key != data[idx].identifier() || value != data[idx].path();
             if key != data[idx].identifier()
                 && let Err(err) = data.rename(idx, key)
             {
-                let msg = err.message().to_owned();
-                return FloatUpdater::new().with_float(Float::Warning(WarningState::new(msg)));
+                return FloatUpdater::new().with_float(Float::Warning(Error::from(err).into()));
             }
             if value != data[idx].path()
                 && let Err(err) = data.relink(idx, &value)
             {
-                let msg = err.message().to_owned();
-                return FloatUpdater::new().with_float(Float::Warning(WarningState::new(msg)));
+                return FloatUpdater::new().with_float(Float::Warning(Error::from(err).into()));
+            }
+            if changed {
+                app.toast("Link saved");
             }
             Some(idx)
         }
@@ -533,7 +638,8 @@ pub fn link_quit_normal(
             None => !state.value().0.is_empty() || !state.value().1.is_empty(),
             Some(idx) => {
                 state.value().0 != app.data[state.from()][idx].identifier()
-                    || state.value().1 != app.data[state.from()][idx].path().as_os_str()
+                    || state.value().1
+                        != escape::escape(app.data[state.from()][idx].path().as_os_str())
             }
         }
     } else {
@@ -3,40 +3,37 @@ use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
 use crate::{
     App,
     app::{
+        external::ExternalAction,
         float::{
-            Float, FloatActionResult, FolderDeleteConfirmCallbackType,
-            LinkDeleteConfirmCallbackType,
-            confirm::{
-                ConfirmChoice, FolderDeleteConfirmState, FolderSaveConfirmState,
-                LinkDeleteConfirmState, LinkSaveConfirmState,
-            },
+            Float, FloatActionResult,
+            confirm::{ConfirmChoice, ConfirmState, FolderSaveConfirmState, LinkSaveConfirmState},
             help::HelpState,
-            warning::{CorruptDataWarningChoice, CorruptDataWarningState, WarningState},
+            import::{ImportPreviewChoice, ImportPreviewState},
+            profile::ProfileState,
+            warning::{
+                BulkEditErrorChoice, BulkEditErrorState, CorruptDataWarningChoice,
+                CorruptDataWarningState, WarningState,
+            },
         },
         key::common,
         message::{ChooseMessage, ConfirmMessage, FloatUpdater, WarningMessage},
-        state::{AppState, NormalState},
+        state::AppState,
     },
-    data::{dir::LinkDir, dirset::LinkDirSet},
 };
 
 #[inline]
-pub fn handle_folder_delete_confirm_key(
-    app: &mut App,
-    key: KeyEvent,
-    state: FolderDeleteConfirmState<FolderDeleteConfirmCallbackType>,
-) -> FloatActionResult {
+pub fn handle_confirm_key(app: &mut App, key: KeyEvent, state: ConfirmState) -> FloatActionResult {
     common::handle_common_key(
         app,
         key,
         state,
-        folder_delete_confirm_key,
-        folder_delete_confirm_message,
-        Float::FolderDeleteConfirm,
+        confirm_key,
+        confirm_message,
+        Float::Confirm,
     )
 }
 
-pub fn folder_delete_confirm_key(key: KeyEvent) -> Option<ConfirmMessage> {
+pub fn confirm_key(key: KeyEvent) -> Option<ConfirmMessage> {
     if key.kind == KeyEventKind::Press {
         match key.code {
             KeyCode::Char('y') | KeyCode::Char('Y') => Some(ConfirmMessage::Yes),
@@ -53,18 +50,14 @@ pub fn folder_delete_confirm_key(key: KeyEvent) -> Option<ConfirmMessage> {
     }
 }
 
-pub fn folder_delete_confirm_message(
+pub fn confirm_message(
     app: &mut App,
-    mut state: FolderDeleteConfirmState<FolderDeleteConfirmCallbackType>,
+    mut state: ConfirmState,
     message: ConfirmMessage,
-) -> FloatUpdater<FolderDeleteConfirmState<FolderDeleteConfirmCallbackType>> {
+) -> FloatUpdater<ConfirmState> {
     match message {
-        ConfirmMessage::Yes => {
-            folder_delete_confirm_call(&mut app.state, &mut app.data, state, ConfirmChoice::Yes)
-        }
-        ConfirmMessage::No | ConfirmMessage::Quit => {
-            folder_delete_confirm_call(&mut app.state, &mut app.data, state, ConfirmChoice::No)
-        }
+        ConfirmMessage::Yes => confirm_call(app, state, ConfirmChoice::Yes),
+        ConfirmMessage::No | ConfirmMessage::Quit => confirm_call(app, state, ConfirmChoice::No),
         ConfirmMessage::SwitchLeft => {
             state.change_choice(ConfirmChoice::Yes);
             FloatUpdater::new().with_state(state)
@@ -79,105 +72,20 @@ pub fn folder_delete_confirm_message(
         }
         ConfirmMessage::Choose => {
             let choice = state.choice();
-            folder_delete_confirm_call(&mut app.state, &mut app.data, state, choice)
+            confirm_call(app, state, choice)
         }
     }
 }
 
-pub fn folder_delete_confirm_call(
-    app_state: &mut AppState,
-    data: &mut LinkDirSet,
-    mut state: FolderDeleteConfirmState<FolderDeleteConfirmCallbackType>,
-    choice: ConfirmChoice,
-) -> FloatUpdater<FolderDeleteConfirmState<FolderDeleteConfirmCallbackType>> {
-    match app_state {
-        AppState::Normal(part) => match &mut **part {
-            NormalState::Folder(folder_state) => {
-                state.change_choice(choice);
-                state.call(folder_state, data);
-                FloatUpdater::new()
-            }
-            _ => FloatUpdater::new(),
-        },
-        _ => FloatUpdater::new(),
-    }
-}
-
-#[inline]
-pub fn handle_link_delete_confirm_key(
+pub fn confirm_call(
     app: &mut App,
-    key: KeyEvent,
-    state: LinkDeleteConfirmState<LinkDeleteConfirmCallbackType>,
-) -> FloatActionResult {
-    common::handle_common_key(
-        app,
-        key,
-        state,
-        folder_delete_confirm_key,
-        link_delete_confirm_message,
-        Float::LinkDeleteConfirm,
-    )
-}
-
-pub fn link_delete_confirm_message(
-    app: &mut App,
-    mut state: LinkDeleteConfirmState<LinkDeleteConfirmCallbackType>,
-    message: ConfirmMessage,
-) -> FloatUpdater<LinkDeleteConfirmState<LinkDeleteConfirmCallbackType>> {
-    match message {
-        ConfirmMessage::Yes => link_delete_confirm_call(
-            &mut app.state,
-            &mut app.data[state.dir_idx()],
-            state,
-            ConfirmChoice::Yes,
-        ),
-        ConfirmMessage::No | ConfirmMessage::Quit => link_delete_confirm_call(
-            &mut app.state,
-            &mut app.data[state.dir_idx()],
-            state,
-            ConfirmChoice::No,
-        ),
-        ConfirmMessage::SwitchLeft => {
-            state.change_choice(ConfirmChoice::Yes);
-            FloatUpdater::new().with_state(state)
-        }
-        ConfirmMessage::SwitchRight => {
-            state.change_choice(ConfirmChoice::No);
-            FloatUpdater::new().with_state(state)
-        }
-        ConfirmMessage::Switch => {
-            state.switch_chioce();
-            FloatUpdater::new().with_state(state)
-        }
-        ConfirmMessage::Choose => {
-            let choice = state.choice();
-            link_delete_confirm_call(
-                &mut app.state,
-                &mut app.data[state.dir_idx()],
-                state,
-                choice,
-            )
-        }
-    }
-}
-
-pub fn link_delete_confirm_call(
-    app_state: &mut AppState,
-    data: &mut LinkDir,
-    mut state: LinkDeleteConfirmState<LinkDeleteConfirmCallbackType>,
+    state: ConfirmState,
     choice: ConfirmChoice,
-) -> FloatUpdater<LinkDeleteConfirmState<LinkDeleteConfirmCallbackType>> {
-    match app_state {
-        AppState::Normal(part) => match &mut **part {
-            NormalState::Link(link_state) => {
-                state.change_choice(choice);
-                state.call(link_state, data);
-                FloatUpdater::new()
-            }
-            _ => FloatUpdater::new(),
-        },
-        _ => FloatUpdater::new(),
+) -> FloatUpdater<ConfirmState> {
+    if choice == ConfirmChoice::Yes {
+        app.apply(state.into_operation());
     }
+    FloatUpdater::new()
 }
 
 #[inline]
@@ -410,6 +318,8 @@ pub fn corrupt_data_warning_message(
                 .with_state(state),
             CorruptDataWarningChoice::NewData => {
                 app.option.save = true;
+                // 没有修改时也要用新的数据替换无法解析的数据文件
+                app.data.mark_modified();
                 FloatUpdater::new()
                     .with_message(ChooseMessage::Quit(true))
                     .with_state(state)
@@ -442,6 +352,222 @@ pub fn corrupt_data_warning_message(
     }
 }
 
+#[inline]
+pub fn handle_bulk_edit_error_key(
+    app: &mut App,
+    key: KeyEvent,
+    state: BulkEditErrorState,
+) -> FloatActionResult {
+    common::handle_common_key(
+        app,
+        key,
+        state,
+        bulk_edit_error_key,
+        bulk_edit_error_message,
+        Float::BulkEditError,
+    )
+}
+
+pub fn bulk_edit_error_key(key: KeyEvent) -> Option<ChooseMessage<bool>> {
+    if key.kind == KeyEventKind::Press {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => {
+                Some(ChooseMessage::Quit(false))
+            }
+            KeyCode::Char('e') | KeyCode::Char('E') => Some(ChooseMessage::Quit(true)),
+            KeyCode::Enter | KeyCode::Char(' ') => Some(ChooseMessage::Choose),
+            KeyCode::Left => Some(ChooseMessage::SwitchLeft),
+            KeyCode::Right => Some(ChooseMessage::SwitchRight),
+            KeyCode::Tab => Some(ChooseMessage::Switch),
+            KeyCode::BackTab => Some(ChooseMessage::SwitchBack),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+pub fn bulk_edit_error_message(
+    app: &mut App,
+    mut state: BulkEditErrorState,
+    message: ChooseMessage<bool>,
+) -> FloatUpdater<BulkEditErrorState> {
+    match message {
+        // true时带着出错的文本重新编辑，false时放弃这次编辑
+        ChooseMessage::Quit(edit) => {
+            if edit {
+                let (folder, text) = state.into_text();
+                app.set_external(ExternalAction::BulkEdit {
+                    folder,
+                    text: Some(text),
+                });
+            }
+            FloatUpdater::new()
+        }
+        ChooseMessage::Choose => {
+            let edit = state.choice() == BulkEditErrorChoice::Edit;
+            FloatUpdater::new()
+                .with_message(ChooseMessage::Quit(edit))
+                .with_state(state)
+        }
+        ChooseMessage::SwitchLeft => {
+            state.switch_left();
+            FloatUpdater::new().with_state(state)
+        }
+        ChooseMessage::SwitchRight => {
+            state.switch_right();
+            FloatUpdater::new().with_state(state)
+        }
+        ChooseMessage::Switch | ChooseMessage::SwitchBack => {
+            state.switch();
+            FloatUpdater::new().with_state(state)
+        }
+        ChooseMessage::SwitchUp | ChooseMessage::SwitchDown => {
+            FloatUpdater::new().with_state(state)
+        }
+    }
+}
+
+pub fn handle_import_preview_key(
+    app: &mut App,
+    key: KeyEvent,
+    state: ImportPreviewState,
+) -> FloatActionResult {
+    common::handle_common_key(
+        app,
+        key,
+        state,
+        import_preview_key,
+        import_preview_message,
+        Float::ImportPreview,
+    )
+}
+
+pub fn import_preview_key(key: KeyEvent) -> Option<ChooseMessage<bool>> {
+    if key.kind == KeyEventKind::Press {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => {
+                Some(ChooseMessage::Quit(false))
+            }
+            KeyCode::Char('y') | KeyCode::Char('Y') => Some(ChooseMessage::Quit(true)),
+            KeyCode::Enter | KeyCode::Char(' ') => Some(ChooseMessage::Choose),
+            KeyCode::Left => Some(ChooseMessage::SwitchLeft),
+            KeyCode::Right => Some(ChooseMessage::SwitchRight),
+            KeyCode::Up | KeyCode::Char('k') => Some(ChooseMessage::SwitchUp),
+            KeyCode::Down | KeyCode::Char('j') => Some(ChooseMessage::SwitchDown),
+            KeyCode::Tab => Some(ChooseMessage::Switch),
+            KeyCode::BackTab => Some(ChooseMessage::SwitchBack),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+pub fn import_preview_message(
+    app: &mut App,
+    mut state: ImportPreviewState,
+    message: ChooseMessage<bool>,
+) -> FloatUpdater<ImportPreviewState> {
+    match message {
+        // true时合并导入的链接，false时放弃导入
+        ChooseMessage::Quit(import) => {
+            if import {
+                let count = crate::import::merge(&mut app.data, state.into_items());
+                app.toast(format!("Imported {count} links"));
+            } else {
+                app.toast("Import cancelled");
+            }
+            FloatUpdater::new()
+        }
+        ChooseMessage::Choose => {
+            let import = state.choice() == ImportPreviewChoice::Import;
+            FloatUpdater::new()
+                .with_message(ChooseMessage::Quit(import))
+                .with_state(state)
+        }
+        ChooseMessage::SwitchLeft => {
+            state.switch_left();
+            FloatUpdater::new().with_state(state)
+        }
+        ChooseMessage::SwitchRight => {
+            state.switch_right();
+            FloatUpdater::new().with_state(state)
+        }
+        ChooseMessage::Switch | ChooseMessage::SwitchBack => {
+            state.switch();
+            FloatUpdater::new().with_state(state)
+        }
+        ChooseMessage::SwitchUp => {
+            state.switch_up();
+            FloatUpdater::new().with_state(state)
+        }
+        ChooseMessage::SwitchDown => {
+            state.switch_down();
+            FloatUpdater::new().with_state(state)
+        }
+    }
+}
+
+#[inline]
+pub fn handle_profile_key(app: &mut App, key: KeyEvent, state: ProfileState) -> FloatActionResult {
+    common::handle_common_key(
+        app,
+        key,
+        state,
+        profile_key,
+        profile_message,
+        Float::Profile,
+    )
+}
+
+pub fn profile_key(key: KeyEvent) -> Option<ChooseMessage<bool>> {
+    if key.kind == KeyEventKind::Press {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => {
+                Some(ChooseMessage::Quit(false))
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => Some(ChooseMessage::Choose),
+            KeyCode::Up | KeyCode::Char('k') => Some(ChooseMessage::SwitchUp),
+            KeyCode::Down | KeyCode::Char('j') => Some(ChooseMessage::SwitchDown),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+pub fn profile_message(
+    app: &mut App,
+    mut state: ProfileState,
+    message: ChooseMessage<bool>,
+) -> FloatUpdater<ProfileState> {
+    match message {
+        // true时切换到选中的配置
+        ChooseMessage::Quit(switch) => {
+            if switch
+                && let Some(name) = state.selected()
+                && state.current() != Some(name)
+            {
+                app.switch_profile(name);
+            }
+            FloatUpdater::new()
+        }
+        ChooseMessage::Choose => FloatUpdater::new()
+            .with_message(ChooseMessage::Quit(true))
+            .with_state(state),
+        ChooseMessage::SwitchUp => {
+            state.switch_up();
+            FloatUpdater::new().with_state(state)
+        }
+        ChooseMessage::SwitchDown => {
+            state.switch_down();
+            FloatUpdater::new().with_state(state)
+        }
+        _ => FloatUpdater::new().with_state(state),
+    }
+}
+
 #[inline]
 pub fn handle_help_key(app: &mut App, key: KeyEvent, state: HelpState) -> FloatActionResult {
     common::handle_common_key(app, key, state, help_key, help_message, Float::Help)
@@ -1,17 +1,32 @@
+use std::path::PathBuf;
+
 use crate::{
     app::{
         data::DataTransfer,
+        external::ExternalAction,
         float::{
             Float,
-            confirm::{ConfirmChoice, FolderDeleteConfirmState, LinkDeleteConfirmState},
+            command::CommandState,
+            confirm::ConfirmState,
             edit::{FolderEditState, LinkEditState},
             help::{HelpEntry, HelpState},
+            profile::ProfileState,
+            warning::WarningState,
         },
-        message::{MessageUpdater, NormalFolderMessage, NormalLinkMessage},
+        message::{AppMessage, MessageUpdater, NormalFolderMessage, NormalLinkMessage},
         normal::{FolderNormalState, LinkNormalState},
+        operation::PendingOperation,
         state::{AppState, NormalState},
     },
-    data::{dir::LinkDir, dirset::LinkDirSet},
+    data::{
+        dir::LinkDir,
+        dirset::{LinkDirMut, LinkDirSet},
+        link::Link,
+        origin::Origin,
+    },
+    error::Error,
+    profile,
+    protocol::Action,
 };
 
 pub fn folder_select(
@@ -119,6 +134,12 @@ pub fn folder_rename(
         return MessageUpdater::new();
     }
     match opt_idx {
+        Some(idx)
+            if idx < data.len()
+                && let Some(origin) = data[idx].shared_origin() =>
+        {
+            read_only(data[idx].identifier(), origin)
+        }
         Some(idx) if idx < data.len() => MessageUpdater::new().with_float(Float::FolderEdit(
             FolderEditState::new(Some(idx)).with_value(data[idx].identifier()),
         )),
@@ -128,29 +149,98 @@ pub fn folder_rename(
 
 pub fn folder_remove(
     state: &mut FolderNormalState,
-    data: &mut LinkDirSet,
+    data: &LinkDirSet,
 ) -> MessageUpdater<NormalFolderMessage> {
     let opt_idx = state.list_state().selected();
     if data.is_empty() {
         return MessageUpdater::new();
     }
     match opt_idx {
+        Some(idx)
+            if idx < data.len()
+                && let Some(origin) = data[idx].shared_origin() =>
+        {
+            read_only(data[idx].identifier(), origin)
+        }
+        Some(idx) if idx < data.len() => MessageUpdater::new().with_float(Float::Confirm(
+            ConfirmState::new(PendingOperation::DeleteFolder {
+                folder: data[idx].identifier().to_string(),
+            }),
+        )),
+        _ => MessageUpdater::new(),
+    }
+}
+
+pub fn folder_bulk_edit(
+    state: &mut FolderNormalState,
+    data: &LinkDirSet,
+) -> MessageUpdater<NormalFolderMessage> {
+    match state.list_state().selected() {
+        Some(idx)
+            if idx < data.len()
+                && let Some(origin) = data[idx].shared_origin() =>
+        {
+            read_only(data[idx].identifier(), origin)
+        }
         Some(idx) if idx < data.len() => {
-            let remove = move |choice, state: &mut FolderNormalState, data: &mut LinkDirSet| {
-                if choice == ConfirmChoice::No {
-                    return;
-                }
-                data.remove(idx);
-                state.select(Some(idx.min(data.len().saturating_sub(1))));
-            };
-            MessageUpdater::new().with_float(Float::FolderDeleteConfirm(
-                FolderDeleteConfirmState::new(Box::new(remove)),
-            ))
+            MessageUpdater::new().with_external(ExternalAction::BulkEdit {
+                folder: data[idx].identifier().to_string(),
+                text: None,
+            })
         }
         _ => MessageUpdater::new(),
     }
 }
 
+/// 把共享的文件夹和其中的链接复制到个人数据，之后可以修改
+pub fn folder_fork(
+    state: &mut FolderNormalState,
+    data: &mut LinkDirSet,
+) -> MessageUpdater<NormalFolderMessage> {
+    if let Some(idx) = state
+        .list_state()
+        .selected()
+        .filter(|&idx| idx < data.len())
+    {
+        data.dir_mut(idx).fork();
+    }
+    MessageUpdater::new()
+}
+
+pub fn folder_edit_data() -> MessageUpdater<NormalFolderMessage> {
+    MessageUpdater::new().with_external(ExternalAction::EditData)
+}
+
+// 展开路径模板，失败时显示警告
+fn resolve(link: &Link) -> Result<PathBuf, WarningState> {
+    link.resolve().map_err(|source| {
+        Error::Unresolved {
+            path: link.path().to_path_buf(),
+            source,
+        }
+        .into()
+    })
+}
+
+// 共享数据只读，提示可以先复制到个人数据
+fn read_only<M: AppMessage>(name: &str, origin: Origin) -> MessageUpdater<M> {
+    MessageUpdater::new().with_float(Float::Warning(WarningState::new(format!(
+        "`{name}` comes from the {} links and is read-only, press <f> to fork it",
+        origin.as_str()
+    ))))
+}
+
+pub fn choose_profile<M: AppMessage>() -> MessageUpdater<M> {
+    match profile::list_profiles() {
+        Ok(profiles) => {
+            MessageUpdater::new().with_float(Float::Profile(ProfileState::new(profiles)))
+        }
+        Err(err) => MessageUpdater::new().with_float(Float::Warning(WarningState::new(format!(
+            "Failed to list profiles: {err}"
+        )))),
+    }
+}
+
 pub fn folder_quit() -> MessageUpdater<NormalFolderMessage> {
     MessageUpdater::new().with_state(AppState::Quit(Box::default()))
 }
@@ -191,6 +281,11 @@ pub fn folder_help() -> MessageUpdater<NormalFolderMessage> {
         HelpEntry::new("<a>", "Add new folder"),
         HelpEntry::new("<r>", "Rename folder"),
         HelpEntry::new("<x>", "Remove folder"),
+        HelpEntry::new("<b>", "Bulk edit links in $EDITOR"),
+        HelpEntry::new("<f>", "Fork shared folder into personal links"),
+        HelpEntry::new("<E>", "Edit data file in $EDITOR"),
+        HelpEntry::new("<P>", "Switch profile"),
+        HelpEntry::new("<Ctrl+Z>", "Suspend"),
         HelpEntry::new("<?>", "Show this help"),
     ]);
     MessageUpdater::new().with_float(Float::Help(help))
@@ -260,7 +355,7 @@ pub fn link_move_down(
 
 pub fn link_switch_up(
     state: &mut LinkNormalState,
-    data: &mut LinkDir,
+    data: &mut LinkDirMut,
 ) -> MessageUpdater<NormalLinkMessage> {
     let opt_idx = state.table_state().selected();
     if data.is_empty() {
@@ -279,7 +374,7 @@ pub fn link_switch_up(
 
 pub fn link_switch_down(
     state: &mut LinkNormalState,
-    data: &mut LinkDir,
+    data: &mut LinkDirMut,
 ) -> MessageUpdater<NormalLinkMessage> {
     let opt_idx = state.table_state().selected();
     if data.is_empty() {
@@ -314,6 +409,9 @@ pub fn link_rename(
         return MessageUpdater::new();
     }
     match opt_idx {
+        Some(idx) if idx < data.len() && !data[idx].origin().is_personal() => {
+            read_only(data[idx].identifier(), data[idx].origin())
+        }
         Some(idx) if idx < data.len() => MessageUpdater::new().with_float(Float::LinkEdit(
             LinkEditState::new(state.folder_list_state().selected().unwrap(), Some(idx))
                 .with_value(data[idx].identifier(), data[idx].path().as_os_str()),
@@ -324,30 +422,101 @@ pub fn link_rename(
 
 pub fn link_remove(
     state: &mut LinkNormalState,
-    data: &mut LinkDir,
+    data: &LinkDir,
 ) -> MessageUpdater<NormalLinkMessage> {
     let opt_idx = state.table_state().selected();
     if data.is_empty() {
         return MessageUpdater::new();
     }
     match opt_idx {
-        Some(idx) if idx < data.len() => {
-            let remove = move |choice, state: &mut LinkNormalState, data: &mut LinkDir| {
-                if choice == ConfirmChoice::No {
-                    return;
-                }
-                data.remove(idx);
-                state.select(Some(idx.min(data.len().saturating_sub(1))));
-            };
-            MessageUpdater::new().with_float(Float::LinkDeleteConfirm(LinkDeleteConfirmState::new(
-                Box::new(remove),
-                state.folder_index(),
-            )))
+        Some(idx) if idx < data.len() && !data[idx].origin().is_personal() => {
+            read_only(data[idx].identifier(), data[idx].origin())
         }
+        Some(idx) if idx < data.len() => MessageUpdater::new().with_float(Float::Confirm(
+            ConfirmState::new(PendingOperation::DeleteLink {
+                folder: data.identifier().to_string(),
+                link: data[idx].identifier().to_string(),
+            }),
+        )),
+        _ => MessageUpdater::new(),
+    }
+}
+
+pub fn link_open_editor(
+    state: &mut LinkNormalState,
+    data: &LinkDir,
+) -> MessageUpdater<NormalLinkMessage> {
+    match state.table_state().selected() {
+        Some(idx) if idx < data.len() => match resolve(&data[idx]) {
+            Ok(path) => MessageUpdater::new().with_external(ExternalAction::EditPath(path)),
+            Err(warning) => MessageUpdater::new().with_float(Float::Warning(warning)),
+        },
+        _ => MessageUpdater::new(),
+    }
+}
+
+/// 以选中的链接退出，由shell执行`action`
+pub fn link_finish(
+    state: &mut LinkNormalState,
+    data: &LinkDir,
+    action: fn(PathBuf) -> Action,
+) -> MessageUpdater<NormalLinkMessage> {
+    match state.table_state().selected() {
+        Some(idx) if idx < data.len() => match resolve(&data[idx]) {
+            Ok(path) => MessageUpdater::new().with_state(AppState::Quit(Box::new(
+                DataTransfer::with_action(action(path)),
+            ))),
+            Err(warning) => MessageUpdater::new().with_float(Float::Warning(warning)),
+        },
         _ => MessageUpdater::new(),
     }
 }
 
+pub fn link_run_command(
+    state: &mut LinkNormalState,
+    data: &LinkDir,
+) -> MessageUpdater<NormalLinkMessage> {
+    match state.table_state().selected() {
+        Some(idx) if idx < data.len() => match resolve(&data[idx]) {
+            Ok(path) => MessageUpdater::new().with_float(Float::Command(CommandState::new(path))),
+            Err(warning) => MessageUpdater::new().with_float(Float::Warning(warning)),
+        },
+        _ => MessageUpdater::new(),
+    }
+}
+
+pub fn link_bulk_edit(
+    _state: &mut LinkNormalState,
+    data: &LinkDir,
+) -> MessageUpdater<NormalLinkMessage> {
+    if let Some(origin) = data.shared_origin() {
+        return read_only(data.identifier(), origin);
+    }
+    MessageUpdater::new().with_external(ExternalAction::BulkEdit {
+        folder: data.identifier().to_string(),
+        text: None,
+    })
+}
+
+/// 把共享的链接复制到个人数据，保存后会覆盖共享数据中的同名链接
+pub fn link_fork(
+    state: &mut LinkNormalState,
+    data: &mut LinkDirMut,
+) -> MessageUpdater<NormalLinkMessage> {
+    if let Some(idx) = state
+        .table_state()
+        .selected()
+        .filter(|&idx| idx < data.len())
+    {
+        data.fork_link(idx);
+    }
+    MessageUpdater::new()
+}
+
+pub fn link_edit_data() -> MessageUpdater<NormalLinkMessage> {
+    MessageUpdater::new().with_external(ExternalAction::EditData)
+}
+
 pub fn link_quit() -> MessageUpdater<NormalLinkMessage> {
     MessageUpdater::new().with_state(AppState::Quit(Box::default()))
 }
@@ -363,9 +532,12 @@ pub fn link_to_link(
     idx: usize,
 ) -> MessageUpdater<NormalLinkMessage> {
     if idx < data.len() {
-        MessageUpdater::new().with_state(AppState::Quit(Box::new(DataTransfer::with_link(
-            data[idx].clone(),
-        ))))
+        match resolve(&data[idx]) {
+            Ok(path) => MessageUpdater::new().with_state(AppState::Quit(Box::new(
+                DataTransfer::with_action(Action::Cd(path)),
+            ))),
+            Err(warning) => MessageUpdater::new().with_float(Float::Warning(warning)),
+        }
     } else {
         MessageUpdater::new()
     }
@@ -386,6 +558,16 @@ pub fn link_help() -> MessageUpdater<NormalLinkMessage> {
         HelpEntry::new("<a>", "Add new link"),
         HelpEntry::new("<r>", "Rename link"),
         HelpEntry::new("<x>", "Remove link"),
+        HelpEntry::new("<e>", "Open link in $EDITOR"),
+        HelpEntry::new("<p>", "Select link with pushd"),
+        HelpEntry::new("<y>", "Print link path"),
+        HelpEntry::new("<o>", "Quit and open link in $EDITOR"),
+        HelpEntry::new("<!>", "Run command in link"),
+        HelpEntry::new("<b>", "Bulk edit links in $EDITOR"),
+        HelpEntry::new("<f>", "Fork shared link into personal links"),
+        HelpEntry::new("<E>", "Edit data file in $EDITOR"),
+        HelpEntry::new("<P>", "Switch profile"),
+        HelpEntry::new("<Ctrl+Z>", "Suspend"),
         HelpEntry::new("<?>", "Show this help"),
     ]);
     MessageUpdater::new().with_float(Float::Help(help))
@@ -1,6 +1,7 @@
 use ratatui::crossterm::event::KeyEvent;
 
 use crate::app::{
+    external::ExternalAction,
     float::{Float, FloatState},
     state::AppState,
 };
@@ -17,6 +18,10 @@ pub enum NormalFolderMessage {
     Append,
     Rename,
     Remove,
+    BulkEdit,
+    Fork,
+    EditData,
+    Profile,
     Quit,
     Item(usize),
     ToDir(usize),
@@ -36,6 +41,15 @@ pub enum NormalLinkMessage {
     Append,
     Rename,
     Remove,
+    OpenEditor,
+    Pushd,
+    PrintPath,
+    EditHere,
+    RunCommand,
+    BulkEdit,
+    Fork,
+    EditData,
+    Profile,
     Quit,
     Item(usize),
     ToLink(usize),
@@ -99,6 +113,7 @@ pub struct MessageUpdater<M: AppMessage> {
     pub message: Option<M>,
     pub state: Option<AppState>,
     pub float: Option<Float>,
+    pub external: Option<ExternalAction>,
 }
 
 impl Default for MessageUpdater<NormalFolderMessage> {
@@ -113,6 +128,7 @@ impl<M: AppMessage> MessageUpdater<M> {
             message: None,
             state: None,
             float: None,
+            external: None,
         }
     }
 
@@ -130,6 +146,11 @@ impl<M: AppMessage> MessageUpdater<M> {
         self.float = Some(float);
         self
     }
+
+    pub fn with_external(mut self, action: ExternalAction) -> Self {
+        self.external = Some(action);
+        self
+    }
 }
 
 pub struct FloatUpdater<S: FloatState> {
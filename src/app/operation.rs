@@ -0,0 +1,102 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::dirset::LinkDirSet;
+
+/// 等待确认的操作，使用标识符而不是下标指定目标，确认前数据改变也不会作用到错误的条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+pub enum PendingOperation {
+    DeleteFolder { folder: String },
+    DeleteLink { folder: String, link: String },
+}
+
+impl PendingOperation {
+    /// 确认框中显示的提示
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            PendingOperation::DeleteFolder { .. } => "Are you sure to DELETE this folder?",
+            PendingOperation::DeleteLink { .. } => "Are you sure to DELETE this link?",
+        }
+    }
+}
+
+impl Display for PendingOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PendingOperation::DeleteFolder { folder } => write!(f, "delete folder `{folder}`"),
+            PendingOperation::DeleteLink { folder, link } => {
+                write!(f, "delete link `{folder}/{link}`")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OperationError {
+    FolderNotFound(String),
+    LinkNotFound(String, String),
+    /// 目标来自共享数据
+    ReadOnly(String),
+}
+
+impl Display for OperationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationError::FolderNotFound(folder) => {
+                write!(f, "Folder `{folder}` no longer exists")
+            }
+            OperationError::LinkNotFound(folder, link) => {
+                write!(f, "Link `{folder}/{link}` no longer exists")
+            }
+            OperationError::ReadOnly(name) => write!(f, "`{name}` is read-only"),
+        }
+    }
+}
+
+impl std::error::Error for OperationError {}
+
+/// 操作完成后被修改的位置，用于调整选中的条目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Applied {
+    FolderRemoved(usize),
+    LinkRemoved { folder: usize, link: usize },
+}
+
+/// 把操作应用到数据上，所有确认后的修改都经过这里
+pub fn apply(
+    data: &mut LinkDirSet,
+    operation: &PendingOperation,
+) -> Result<Applied, OperationError> {
+    match operation {
+        PendingOperation::DeleteFolder { folder } => {
+            let idx = find_folder(data, folder)?;
+            if data[idx].shared_origin().is_some() {
+                return Err(OperationError::ReadOnly(folder.clone()));
+            }
+            data.remove(idx);
+            Ok(Applied::FolderRemoved(idx))
+        }
+        PendingOperation::DeleteLink { folder, link } => {
+            let dir_idx = find_folder(data, folder)?;
+            let mut dir = data.dir_mut(dir_idx);
+            let idx = dir
+                .index_of(link)
+                .ok_or_else(|| OperationError::LinkNotFound(folder.clone(), link.clone()))?;
+            if !dir[idx].origin().is_personal() {
+                return Err(OperationError::ReadOnly(format!("{folder}/{link}")));
+            }
+            dir.remove(idx);
+            Ok(Applied::LinkRemoved {
+                folder: dir_idx,
+                link: idx,
+            })
+        }
+    }
+}
+
+fn find_folder(data: &LinkDirSet, folder: &str) -> Result<usize, OperationError> {
+    data.index_of(folder)
+        .ok_or_else(|| OperationError::FolderNotFound(folder.to_string()))
+}
@@ -0,0 +1,110 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, LineWriter, Write},
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+use ratatui::crossterm::event::Event;
+use serde::{Deserialize, Serialize};
+
+use crate::app::event::EventSource;
+
+/// 录制文件中的一行，`time`为距离开始录制的毫秒数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub time: u64,
+    pub event: Event,
+}
+
+/// 把另一个事件来源返回的事件逐行写入文件，每个事件都会立即写入，程序崩溃时也不会丢失
+#[derive(Debug)]
+pub struct Recorder<S: EventSource> {
+    source: S,
+    start: Instant,
+    file: LineWriter<File>,
+}
+
+impl<S: EventSource> Recorder<S> {
+    pub fn create(source: S, path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            source,
+            start: Instant::now(),
+            file: LineWriter::new(File::create(path)?),
+        })
+    }
+}
+
+impl<S: EventSource> EventSource for Recorder<S> {
+    fn poll_event(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        let event = self.source.poll_event(timeout)?;
+        if let Some(event) = &event {
+            let line = RecordedEvent {
+                time: self.start.elapsed().as_millis() as u64,
+                event: event.clone(),
+            };
+            serde_json::to_writer(&mut self.file, &line)?;
+            self.file.write_all(b"\n")?;
+        }
+        Ok(event)
+    }
+}
+
+/// 按照录制时的间隔重新返回录制的事件，中间的tick也会按时出现
+#[derive(Debug)]
+pub struct Replay {
+    events: std::vec::IntoIter<RecordedEvent>,
+    next: Option<RecordedEvent>,
+    start: Instant,
+}
+
+impl Replay {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut events = Vec::new();
+        for (idx, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = serde_json::from_str(&line).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} line {}: {err}", path.display(), idx + 1),
+                )
+            })?;
+            events.push(event);
+        }
+        Ok(Self::new(events))
+    }
+
+    pub fn new(events: Vec<RecordedEvent>) -> Self {
+        let mut events = events.into_iter();
+        Self {
+            next: events.next(),
+            events,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl EventSource for Replay {
+    fn poll_event(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        let Some(next) = &self.next else {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "replay finished before quit",
+            ));
+        };
+        let due = Duration::from_millis(next.time);
+        let wait = due.saturating_sub(self.start.elapsed());
+        if wait > timeout {
+            thread::sleep(timeout);
+            return Ok(None);
+        }
+        thread::sleep(wait);
+        let event = self.next.take().map(|recorded| recorded.event);
+        self.next = self.events.next();
+        Ok(event)
+    }
+}
@@ -28,6 +28,17 @@ impl AppState {
         Self::Normal(Box::new(NormalState::Folder(FolderNormalState::new())))
     }
 
+    /// 用于日志的状态名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppState::Normal(part) => match &**part {
+                NormalState::Folder(_) => "folder",
+                NormalState::Link(_) => "link",
+            },
+            AppState::Quit(_) => "quit",
+        }
+    }
+
     pub fn folder_list_state(&self) -> Option<&ListState> {
         match self {
             AppState::Normal(part) => match &**part {
@@ -0,0 +1,416 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    fs, io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::app::event::TaskResult;
+
+/// 对一个路径的文件系统查询，在NFS或者sshfs上可能需要很久
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Query {
+    pub path: PathBuf,
+    pub kind: QueryKind,
+}
+
+impl Query {
+    pub fn new(path: PathBuf, kind: QueryKind) -> Self {
+        Self { path, kind }
+    }
+
+    /// 在后台线程中执行
+    pub fn run(&self) -> QueryResult {
+        match self.kind {
+            QueryKind::Exists => QueryResult::Exists(self.path.try_exists().unwrap_or(false)),
+            QueryKind::Preview => QueryResult::Preview(Preview::read(&self.path).ok()),
+            QueryKind::GitStatus => QueryResult::GitStatus(GitStatus::read(&self.path)),
+            QueryKind::Size => QueryResult::Size(Size::read(&self.path)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryKind {
+    Exists,
+    Preview,
+    GitStatus,
+    Size,
+}
+
+impl QueryKind {
+    /// 需要遍历目录或者启动进程的查询，在单独的线程中执行
+    pub fn is_slow(self) -> bool {
+        matches!(self, QueryKind::GitStatus | QueryKind::Size)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryResult {
+    Exists(bool),
+    /// 不是目录或者无法读取时为`None`
+    Preview(Option<Preview>),
+    /// 不在git仓库中或者没有安装git时为`None`
+    GitStatus(Option<GitStatus>),
+    Size(Size),
+}
+
+/// 目录中的前几项，按照名称排序
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preview {
+    pub total: usize,
+    pub entries: Vec<String>,
+}
+
+impl Preview {
+    pub const MAX_ENTRIES: usize = 8;
+
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let mut names = fs::read_dir(path)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        let total = names.len();
+        names.truncate(Self::MAX_ENTRIES);
+        Ok(Self {
+            total,
+            entries: names,
+        })
+    }
+}
+
+impl Display for Preview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} entries", self.total)?;
+        if !self.entries.is_empty() {
+            write!(f, ": {}", self.entries.join(", "))?;
+            if self.total > self.entries.len() {
+                f.write_str(", …")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitStatus {
+    pub branch: String,
+    /// 有未提交的修改，不包括未跟踪的文件
+    pub dirty: bool,
+}
+
+impl GitStatus {
+    pub fn read(path: &Path) -> Option<Self> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(path)
+            .args(["status", "--porcelain", "--branch", "--untracked-files=no"])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        Self::parse(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// 第一行为`## main...origin/main [ahead 1]`，之后每行是一个修改的文件
+    pub fn parse(status: &str) -> Option<Self> {
+        let mut lines = status.lines();
+        let head = lines.next()?.strip_prefix("## ")?;
+        let head = head.strip_prefix("No commits yet on ").unwrap_or(head);
+        let head = head.split(' ').next().unwrap_or(head);
+        let branch = head.split_once("...").map_or(head, |(branch, _)| branch);
+        Some(Self {
+            branch: branch.to_string(),
+            dirty: lines.next().is_some(),
+        })
+    }
+}
+
+impl Display for GitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.branch)?;
+        if self.dirty {
+            f.write_str("*")?;
+        }
+        Ok(())
+    }
+}
+
+/// 目录中所有文件的大小，文件太多时只统计一部分
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Size {
+    pub bytes: u64,
+    pub complete: bool,
+}
+
+impl Size {
+    pub const MAX_ENTRIES: usize = 100_000;
+
+    /// 不跟随符号链接，无法读取的目录跳过
+    pub fn read(path: &Path) -> Self {
+        let mut bytes = 0;
+        let mut count = 0;
+        let mut dirs = vec![path.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                count += 1;
+                if count > Self::MAX_ENTRIES {
+                    return Self {
+                        bytes,
+                        complete: false,
+                    };
+                }
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    dirs.push(entry.path());
+                } else {
+                    bytes += metadata.len();
+                }
+            }
+        }
+        Self {
+            bytes,
+            complete: true,
+        }
+    }
+}
+
+impl Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+        if !self.complete {
+            f.write_str(">")?;
+        }
+        let mut size = self.bytes as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit + 1 < UNITS.len() {
+            size /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            write!(f, "{} {}", self.bytes, UNITS[0])
+        } else {
+            write!(f, "{size:.1} {}", UNITS[unit])
+        }
+    }
+}
+
+type Runner = Arc<dyn Fn(&Query) -> QueryResult + Send + Sync>;
+
+/// 固定数量的后台线程，结果通过事件循环的通道返回
+///
+/// 慢的查询使用另一组线程，不会占满检查路径是否存在的线程。
+/// 卡住的查询无法取消，退出时不会等待线程结束
+#[derive(Debug)]
+pub struct WorkerPool {
+    fast: Sender<Query>,
+    slow: Sender<Query>,
+}
+
+impl WorkerPool {
+    /// 两组线程各有`size`个
+    pub fn new(size: usize, results: Sender<TaskResult>) -> Self {
+        Self::with_runner(size, results, Query::run)
+    }
+
+    /// 使用`run`代替`Query::run`执行查询，用于测试
+    pub fn with_runner<F>(size: usize, results: Sender<TaskResult>, run: F) -> Self
+    where
+        F: Fn(&Query) -> QueryResult + Send + Sync + 'static,
+    {
+        let run: Runner = Arc::new(run);
+        Self {
+            fast: spawn_workers(size, &results, &run),
+            slow: spawn_workers(size, &results, &run),
+        }
+    }
+
+    /// 线程数量与CPU数量有关，大部分时间在等待文件系统，所以至少2个
+    pub fn default_size() -> usize {
+        thread::available_parallelism().map_or(2, |n| n.get().clamp(2, 4))
+    }
+
+    pub fn submit(&self, query: Query) {
+        let jobs = if query.kind.is_slow() {
+            &self.slow
+        } else {
+            &self.fast
+        };
+        let _ = jobs.send(query);
+    }
+}
+
+fn spawn_workers(size: usize, results: &Sender<TaskResult>, run: &Runner) -> Sender<Query> {
+    let (jobs, receiver) = mpsc::channel::<Query>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..size.max(1) {
+        let receiver = Arc::clone(&receiver);
+        let results = results.clone();
+        let run = Arc::clone(run);
+        thread::spawn(move || worker(&receiver, &results, &*run));
+    }
+    jobs
+}
+
+fn worker(
+    jobs: &Mutex<Receiver<Query>>,
+    results: &Sender<TaskResult>,
+    run: &(dyn Fn(&Query) -> QueryResult + Send + Sync),
+) {
+    loop {
+        // 只在取任务时持有锁
+        let query = match jobs.lock() {
+            Ok(jobs) => jobs.recv(),
+            Err(_) => return,
+        };
+        let Ok(query) = query else {
+            return;
+        };
+        let result = run(&query);
+        if results.send(TaskResult::Query { query, result }).is_err() {
+            return;
+        }
+    }
+}
+
+/// 查询在界面上的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryState<'a> {
+    /// 还没有结果，包括还没有提交的查询
+    Checking,
+    /// 超过`timeout`仍然没有结果
+    TimedOut,
+    /// 结果过期后重新查询时仍然返回之前的结果
+    Done(&'a QueryResult),
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    result: Option<(QueryResult, Instant)>,
+    pending: Option<Instant>,
+}
+
+/// 每个查询的结果，超过`ttl`之后重新查询
+///
+/// 同一个查询同时只有一个在进行，检查是否存在不会等待同一个路径上慢的查询。
+/// 卡住的查询超过`timeout + ttl`之后重新提交一次，同一个路径最多占用[`Self::MAX_IN_FLIGHT`]个线程
+#[derive(Debug)]
+pub struct QueryCache {
+    entries: HashMap<Query, CacheEntry>,
+    // 每个路径正在进行的查询数量，包括超时之后仍然没有返回的
+    in_flight: HashMap<PathBuf, usize>,
+    ttl: Duration,
+    timeout: Duration,
+}
+
+impl Default for QueryCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QueryCache {
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(30);
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+    /// 超时的查询重新提交时，同一个路径正在进行的查询不能超过这个数量
+    pub const MAX_IN_FLIGHT: usize = 2;
+
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            in_flight: HashMap::new(),
+            ttl: Self::DEFAULT_TTL,
+            timeout: Self::DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn get(&self, query: &Query, now: Instant) -> QueryState<'_> {
+        match self.entries.get(query) {
+            Some(CacheEntry {
+                result: Some((result, _)),
+                ..
+            }) => QueryState::Done(result),
+            Some(CacheEntry {
+                pending: Some(since),
+                ..
+            }) if now.duration_since(*since) >= self.timeout => QueryState::TimedOut,
+            _ => QueryState::Checking,
+        }
+    }
+
+    /// 没有结果或者结果已经过期，并且这个查询没有在进行
+    ///
+    /// 超时的查询在`timeout + ttl`之后才重新提交，避免卡住的路径占满所有线程
+    pub fn needs_query(&self, query: &Query, now: Instant) -> bool {
+        match self.entries.get(query) {
+            Some(CacheEntry {
+                pending: Some(since),
+                ..
+            }) => {
+                now.duration_since(*since) >= self.timeout + self.ttl
+                    && self.in_flight(&query.path) < Self::MAX_IN_FLIGHT
+            }
+            Some(CacheEntry {
+                result: Some((_, at)),
+                ..
+            }) => now.duration_since(*at) >= self.ttl,
+            _ => true,
+        }
+    }
+
+    fn in_flight(&self, path: &Path) -> usize {
+        self.in_flight.get(path).copied().unwrap_or(0)
+    }
+
+    pub fn start(&mut self, query: Query, now: Instant) {
+        *self.in_flight.entry(query.path.clone()).or_insert(0) += 1;
+        self.entries
+            .entry(query)
+            .or_insert(CacheEntry {
+                result: None,
+                pending: None,
+            })
+            .pending = Some(now);
+    }
+
+    /// 重新提交过的查询可能返回多次，每次都更新结果
+    pub fn finish(&mut self, query: Query, result: QueryResult, now: Instant) {
+        if let Some(count) = self.in_flight.get_mut(&query.path) {
+            *count -= 1;
+            if *count == 0 {
+                self.in_flight.remove(&query.path);
+            }
+        }
+        self.entries.insert(
+            query,
+            CacheEntry {
+                result: Some((result, now)),
+                pending: None,
+            },
+        );
+    }
+}
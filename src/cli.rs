@@ -0,0 +1,121 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use clap_complete::ArgValueCandidates;
+use tracing::level_filters::LevelFilter;
+
+use crate::{
+    complete::{self, CompletionShell},
+    export::Format,
+    import::Source,
+    init::Shell,
+    layer::Layers,
+    log::LOG_ENV,
+    profile::{DATA_ENV, PROFILE_ENV},
+    term::{Height, ViewportMode},
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Manage directory links in a TUI")]
+pub struct Cli {
+    /// File to write the selected path to
+    pub output: Option<PathBuf>,
+
+    /// Render in an inline viewport below the prompt instead of the alternate screen,
+    /// e.g. `--height 20` or `--height 40%`
+    #[arg(long, global = true, value_parser = Height::parse)]
+    pub height: Option<Height>,
+
+    /// Use this data file instead of a profile
+    #[arg(long, global = true, env = DATA_ENV, value_name = "PATH")]
+    pub data: Option<PathBuf>,
+
+    /// Use a named profile, each profile has its own links
+    #[arg(long, global = true, env = PROFILE_ENV, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Read-only team file merged into the links, can be repeated; also read from
+    /// `DIR_LINK_TEAM` as a `PATH`-style list
+    #[arg(long, global = true, value_name = "PATH")]
+    pub team: Vec<PathBuf>,
+
+    /// Don't merge the `.dirlink.json` found in the current directory or its parents
+    #[arg(long, global = true)]
+    pub no_project: bool,
+
+    /// Record every terminal event with its timing to this file, for bug reports
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Replay a file written by `--record` against a copy of the data file
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// Write logs to a daily rotated file in the data directory, or next to the
+    /// `--data` file: off, error, warn, info, debug or trace
+    #[arg(long, global = true, env = LOG_ENV, value_name = "LEVEL")]
+    pub log_level: Option<LevelFilter>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Import bookmarks from another tool and preview them before merging
+    Import {
+        source: Source,
+
+        /// Read bookmarks from this file instead of the tool's default location
+        #[arg(long)]
+        from: Option<PathBuf>,
+
+        /// Folder to merge the bookmarks into, defaults to the name of the source
+        #[arg(long)]
+        folder: Option<String>,
+    },
+    /// Print the links in a shell-native format, e.g. `dir_link export hash >> ~/.zshrc`
+    Export {
+        format: Format,
+
+        /// Only export this folder, using the link identifiers as names
+        #[arg(long)]
+        folder: Option<String>,
+    },
+    /// Print shell integration, e.g. `eval "$(dir_link init bash)"`
+    Init {
+        shell: Shell,
+
+        /// Name of the function that opens the picker or jumps to `folder/link`
+        #[arg(long, default_value = "dlk")]
+        cmd: String,
+    },
+    /// Jump to a link without opening the TUI
+    Go {
+        /// Link to jump to, as `folder/link`
+        #[arg(add = ArgValueCandidates::new(complete::link_candidates))]
+        target: String,
+    },
+    /// List all links as `folder/link`
+    List,
+    /// Print the completion script, e.g. `source <(dir_link completions bash)`
+    Completions { shell: CompletionShell },
+}
+
+impl Cli {
+    pub fn layers(&self) -> Layers {
+        let layers = Layers::discover(self.team.clone());
+        if self.no_project {
+            layers.without_project()
+        } else {
+            layers
+        }
+    }
+
+    pub fn viewport(&self) -> ViewportMode {
+        match self.height {
+            Some(height) => ViewportMode::Inline(height),
+            None => ViewportMode::Fullscreen,
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::io;
+
+use clap::CommandFactory;
+use clap_complete::{
+    CompletionCandidate,
+    env::{Bash, CompleteEnv, Elvish, EnvCompleter, Fish, Powershell, Zsh},
+};
+
+use crate::{cli::Cli, layer::Layers, profile::DataSource, store};
+
+/// shell通过该环境变量调用`dir_link`获取补全结果
+pub const COMPLETE_VAR: &str = "DIR_LINK_COMPLETE";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    Elvish,
+    Powershell,
+}
+
+impl CompletionShell {
+    fn completer(&self) -> &'static dyn EnvCompleter {
+        match self {
+            CompletionShell::Bash => &Bash,
+            CompletionShell::Zsh => &Zsh,
+            CompletionShell::Fish => &Fish,
+            CompletionShell::Elvish => &Elvish,
+            CompletionShell::Powershell => &Powershell,
+        }
+    }
+}
+
+/// 输出注册补全的脚本，补全时shell会带着[`COMPLETE_VAR`]重新调用`dir_link`
+pub fn write_registration(shell: CompletionShell, buf: &mut dyn io::Write) -> io::Result<()> {
+    let bin = env!("CARGO_PKG_NAME");
+    shell
+        .completer()
+        .write_registration(COMPLETE_VAR, bin, bin, bin, buf)
+}
+
+/// 设置了[`COMPLETE_VAR`]时输出补全结果并退出，否则直接返回
+pub fn complete() {
+    CompleteEnv::with_factory(Cli::command)
+        .var(COMPLETE_VAR)
+        .complete();
+}
+
+/// `folder/link`的补全候选，读取数据文件失败时没有候选
+pub fn link_candidates() -> Vec<CompletionCandidate> {
+    let Ok(source) = DataSource::from_env() else {
+        return Vec::new();
+    };
+    // 补全时不能像`Store::open`那样创建数据文件
+    let mut data = store::read(source.path()).unwrap_or_default();
+    // 补全时无法得到`--team`，只使用环境变量和项目文件
+    let _ = Layers::discover(Vec::new()).merge_into(&mut data);
+    data.iter()
+        .flat_map(|dir| {
+            dir.iter().map(move |link| {
+                CompletionCandidate::new(format!("{}/{}", dir.identifier(), link.identifier()))
+                    .help(Some(link.path().display().to_string().into()))
+            })
+        })
+        .collect()
+}
@@ -1,4 +1,9 @@
+pub mod bulk;
 pub mod dir;
 pub mod dirset;
 pub mod err;
+pub mod escape;
+pub mod index;
 pub mod link;
+pub mod origin;
+pub mod template;
@@ -0,0 +1,166 @@
+use std::fmt::{self, Display};
+use std::path::PathBuf;
+
+use super::dir::LinkDir;
+use super::dirset::LinkDirMut;
+use super::escape;
+use super::link::Link;
+
+/// 每个链接一行，格式为`identifier<TAB>path`
+pub fn dump(dir: &LinkDir) -> String {
+    dir.iter()
+        .map(|link| {
+            format!(
+                "{}\t{}\n",
+                link.identifier(),
+                escape::escape(link.path().as_os_str())
+            )
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    line: usize,
+    message: String,
+}
+
+impl ParseError {
+    fn new<S: Into<String>>(line: usize, message: S) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+
+    /// 从1开始的行号
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 解析编辑后的文本，空行会被忽略
+pub fn parse(text: &str) -> Result<Vec<Link>, ParseError> {
+    let mut links: Vec<Link> = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line_no = idx + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some((identifier, path)) = line.split_once('\t') else {
+            return Err(ParseError::new(line_no, "expected `identifier<TAB>path`"));
+        };
+        if links.iter().any(|link| link.identifier() == identifier) {
+            return Err(ParseError::new(
+                line_no,
+                format!("duplicated link identifier `{identifier}`"),
+            ));
+        }
+        let link = Link::builder(identifier, &PathBuf::from(escape::unescape(path)))
+            .map_err(|err| ParseError::new(line_no, err.to_string()))?;
+        links.push(link);
+    }
+    Ok(links)
+}
+
+/// 编辑前后的差异，按照标识符匹配原有的链接，标识符不同时再按照路径匹配
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Summary {
+    pub renamed: usize,
+    pub relinked: usize,
+    pub removed: usize,
+    pub added: usize,
+    pub reordered: bool,
+}
+
+impl Summary {
+    pub fn diff(old: &[Link], new: &[Link]) -> Self {
+        let mut summary = Self::default();
+        let mut matched = vec![false; old.len()];
+        // new中每一项对应的old的下标
+        let mut origin: Vec<Option<usize>> = vec![None; new.len()];
+
+        for (new_idx, link) in new.iter().enumerate() {
+            if let Some(old_idx) = old
+                .iter()
+                .position(|old| old.identifier() == link.identifier())
+            {
+                matched[old_idx] = true;
+                origin[new_idx] = Some(old_idx);
+                if old[old_idx].path() != link.path() {
+                    summary.relinked += 1;
+                }
+            }
+        }
+        for (new_idx, link) in new.iter().enumerate() {
+            if origin[new_idx].is_some() {
+                continue;
+            }
+            let renamed = old
+                .iter()
+                .enumerate()
+                .position(|(old_idx, old)| !matched[old_idx] && old.path() == link.path());
+            match renamed {
+                Some(old_idx) => {
+                    matched[old_idx] = true;
+                    origin[new_idx] = Some(old_idx);
+                    summary.renamed += 1;
+                }
+                None => summary.added += 1,
+            }
+        }
+        summary.removed = matched.iter().filter(|&&matched| !matched).count();
+
+        let order: Vec<_> = origin.into_iter().flatten().collect();
+        summary.reordered = order.windows(2).any(|pair| pair[0] > pair[1]);
+        summary
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("No changes");
+        }
+        let parts = [
+            (self.renamed, "renamed"),
+            (self.relinked, "relinked"),
+            (self.removed, "removed"),
+            (self.added, "added"),
+        ];
+        let mut parts: Vec<_> = parts
+            .into_iter()
+            .filter(|&(count, _)| count > 0)
+            .map(|(count, action)| format!("{count} {action}"))
+            .collect();
+        if self.reordered {
+            parts.push(String::from("reordered"));
+        }
+        f.write_str(&parts.join(", "))
+    }
+}
+
+/// 解析编辑后的文本并应用到`dir`上，出错时`dir`保持不变
+pub fn apply(dir: &mut LinkDirMut, text: &str) -> Result<Summary, ParseError> {
+    let links = parse(text)?;
+    let summary = Summary::diff(dir, &links);
+    dir.set_links(links)
+        .expect("identifiers are checked while parsing");
+    Ok(summary)
+}
@@ -1,20 +1,30 @@
-use std::ops::DerefMut;
 use std::path::Path;
-use std::{collections::HashSet, ops::Deref};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+};
 
-use serde::de::{self, Visitor};
-use serde::ser::SerializeStruct;
-use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde-json")]
+use serde::{
+    self, Deserialize, Deserializer, Serialize, Serializer,
+    de::{self, Visitor},
+    ser::SerializeStruct,
+};
 
 use super::err::{Error, ErrorKind};
 use super::link::Link;
+use super::origin::Origin;
 
+/// 链接的标识符同时保存在`index`中，因此只能通过这里的方法修改链接，不会提供`&mut Link`
 #[derive(Debug, PartialEq, Eq)]
 pub struct LinkDir {
     // 此处不使用HashMap是因为需要保持插入顺序
     map: Vec<Link>,
-    set: HashSet<String>,
+    // 标识符到`map`中位置的索引
+    index: HashMap<String, usize>,
     identifier: String,
+    // 同名的文件夹出现在多层时使用优先级最高的一层
+    origin: Origin,
 }
 
 impl LinkDir {
@@ -27,8 +37,9 @@ impl LinkDir {
         }
         Ok(Self {
             map: Vec::new(),
-            set: HashSet::new(),
+            index: HashMap::new(),
             identifier: String::from(identifier),
+            origin: Origin::Personal,
         })
     }
 
@@ -36,7 +47,8 @@ impl LinkDir {
         &self.identifier
     }
 
-    pub fn set_identifier(&mut self, identifier: &str) -> Result<(), Error> {
+    // 文件夹的标识符同样保存在`LinkDirSet`中，只能通过`LinkDirSet::rename`修改
+    pub(super) fn set_identifier(&mut self, identifier: &str) -> Result<(), Error> {
         if identifier.is_empty() {
             return Err(Error::new(
                 ErrorKind::InvaildIdentifier,
@@ -47,8 +59,72 @@ impl LinkDir {
         Ok(())
     }
 
-    pub fn set(&self) -> &HashSet<String> {
-        &self.set
+    /// 标识符对应的链接的位置
+    pub fn index_of(&self, identifier: &str) -> Option<usize> {
+        self.index.get(identifier).copied()
+    }
+
+    pub fn find(&self, identifier: &str) -> Option<&Link> {
+        self.index_of(identifier).map(|idx| &self.map[idx])
+    }
+
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    /// 文件夹本身或者其中的链接来自共享数据时返回来源，此时不能重命名、删除或者批量编辑
+    pub fn shared_origin(&self) -> Option<Origin> {
+        std::iter::once(self.origin)
+            .chain(self.map.iter().map(|link| link.origin()))
+            .find(|origin| !origin.is_personal())
+    }
+
+    /// 同时设置文件夹和其中所有链接的来源
+    pub fn set_origin(&mut self, origin: Origin) {
+        self.origin = origin;
+        self.map.iter_mut().for_each(|link| link.set_origin(origin));
+    }
+
+    /// 把文件夹和其中的所有链接复制到个人数据
+    pub fn fork(&mut self) {
+        self.set_origin(Origin::Personal);
+    }
+
+    /// 把一个链接复制到个人数据，保存后个人数据中的同名链接会覆盖共享的链接
+    pub fn fork_link(&mut self, idx: usize) {
+        self.origin = Origin::Personal;
+        self.map[idx].set_origin(Origin::Personal);
+    }
+
+    /// 把另一层的文件夹合并进来，已经存在的链接保持不变
+    pub fn merge_layer(&mut self, other: LinkDir, origin: Origin) {
+        for mut link in other.map {
+            if !self.index.contains_key(link.identifier()) {
+                link.set_origin(origin);
+                self.index
+                    .insert(link.identifier().to_string(), self.map.len());
+                self.map.push(link);
+            }
+        }
+    }
+
+    /// 只包含个人数据的副本，文件夹中没有个人链接并且来自共享数据时返回`None`
+    pub fn personal(&self) -> Option<LinkDir> {
+        let links: Vec<_> = self
+            .map
+            .iter()
+            .filter(|link| link.origin().is_personal())
+            .cloned()
+            .collect();
+        if links.is_empty() && !self.origin.is_personal() {
+            return None;
+        }
+        Some(Self {
+            index: index_of_links(&links),
+            map: links,
+            identifier: self.identifier.clone(),
+            origin: Origin::Personal,
+        })
     }
 
     pub fn map(&self) -> &Vec<Link> {
@@ -56,13 +132,16 @@ impl LinkDir {
     }
 
     pub fn push(&mut self, link: Link) -> Result<(), Error> {
-        if self.set.contains(link.identifier()) {
+        if self.index.contains_key(link.identifier()) {
+            let identifier = link.identifier().to_string();
             return Err(Error::new(
                 ErrorKind::DuplicatedLinkIdentifier(link),
                 "Same link name already exists",
-            ));
+            )
+            .with_subject(identifier));
         }
-        self.set.insert(link.identifier().to_string());
+        self.index
+            .insert(link.identifier().to_string(), self.map.len());
         self.map.push(link);
         Ok(())
     }
@@ -73,21 +152,25 @@ impl LinkDir {
         }
 
         let item = self.map.remove(index);
-        self.set.remove(item.identifier());
+        self.index.remove(item.identifier());
+        self.reindex(index);
         item
     }
 
     pub fn rename(&mut self, idx: usize, identifier: &str) -> Result<(), Error> {
-        if self.set.contains(identifier) {
+        if self.index.contains_key(identifier) {
             return Err(Error::new(
                 ErrorKind::DuplicatedIdentifier,
                 "Same link name already exists",
-            ));
+            )
+            .with_subject(identifier));
         }
+        // 先修改标识符，新的标识符无效时集合保持不变
         let link = &mut self.map[idx];
-        self.set.remove(link.identifier());
+        let old = link.identifier().to_string();
         link.set_identifier(identifier)?;
-        self.set.insert(identifier.to_string());
+        self.index.remove(&old);
+        self.index.insert(identifier.to_string(), idx);
         Ok(())
     }
 
@@ -97,9 +180,54 @@ impl LinkDir {
 
     pub fn swap(&mut self, a: usize, b: usize) {
         self.map.swap(a, b);
+        self.index.insert(self.map[a].identifier().to_string(), a);
+        self.index.insert(self.map[b].identifier().to_string(), b);
+    }
+
+    /// 整体替换所有链接，出现重复的标识符时保持不变
+    pub fn set_links(&mut self, links: Vec<Link>) -> Result<(), Error> {
+        let mut set = HashSet::with_capacity(links.len());
+        for link in &links {
+            if !set.insert(link.identifier()) {
+                return Err(Error::new(
+                    ErrorKind::DuplicatedIdentifier,
+                    "Same link name already exists",
+                )
+                .with_subject(link.identifier()));
+            }
+        }
+        self.index = index_of_links(&links);
+        self.map = links;
+        Ok(())
+    }
+
+    /// `index`与链接的标识符和位置一一对应，并且标识符不为空
+    pub fn is_consistent(&self) -> bool {
+        !self.identifier.is_empty()
+            && self.index.len() == self.map.len()
+            && self.map.iter().enumerate().all(|(idx, link)| {
+                !link.identifier().is_empty() && self.index_of(link.identifier()) == Some(idx)
+            })
+    }
+
+    // 删除之后`from`及之后的链接位置都改变了
+    fn reindex(&mut self, from: usize) {
+        for (idx, link) in self.map.iter().enumerate().skip(from) {
+            if let Some(pos) = self.index.get_mut(link.identifier()) {
+                *pos = idx;
+            }
+        }
     }
 }
 
+fn index_of_links(links: &[Link]) -> HashMap<String, usize> {
+    links
+        .iter()
+        .enumerate()
+        .map(|(idx, link)| (link.identifier().to_string(), idx))
+        .collect()
+}
+
 impl Deref for LinkDir {
     type Target = [Link];
     fn deref(&self) -> &Self::Target {
@@ -107,12 +235,7 @@ impl Deref for LinkDir {
     }
 }
 
-impl DerefMut for LinkDir {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        self.map.as_mut_slice()
-    }
-}
-
+#[cfg(feature = "serde-json")]
 impl Serialize for LinkDir {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         let mut state = serializer.serialize_struct("LinkDir", 2)?;
@@ -122,6 +245,7 @@ impl Serialize for LinkDir {
     }
 }
 
+#[cfg(feature = "serde-json")]
 impl<'de> Deserialize<'de> for LinkDir {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         #[derive(Deserialize)]
@@ -1,15 +1,24 @@
-use std::ops::DerefMut;
-use std::{collections::HashSet, ops::Deref};
+use std::path::Path;
+use std::{collections::HashMap, ops::Deref};
 
+#[cfg(feature = "serde-json")]
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use super::dir::LinkDir;
 use super::err::{Error, ErrorKind};
+use super::link::Link;
+use super::origin::Origin;
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default)]
 pub struct LinkDirSet {
     map: Vec<LinkDir>,
-    set: HashSet<String>,
+    // 标识符到`map`中位置的索引
+    index: HashMap<String, usize>,
+    // 个人数据每次修改后递增，合并共享数据不会改变
+    revision: u64,
+    saved: u64,
+    // 读取或者保存时数据文件内容的哈希，保存前用来检查文件是否被其他进程修改过
+    file_hash: Option<u64>,
 }
 
 impl LinkDirSet {
@@ -21,19 +30,51 @@ impl LinkDirSet {
         &self.map
     }
 
-    pub fn set(&self) -> &HashSet<String> {
-        &self.set
+    /// 标识符对应的文件夹的位置
+    pub fn index_of(&self, identifier: &str) -> Option<usize> {
+        self.index.get(identifier).copied()
+    }
+
+    pub fn find(&self, identifier: &str) -> Option<&LinkDir> {
+        self.index_of(identifier).map(|idx| &self.map[idx])
+    }
+
+    /// 上一次保存或者读取之后个人数据是否被修改过
+    pub fn is_modified(&self) -> bool {
+        self.revision != self.saved
+    }
+
+    /// 与数据文件无关时为`None`，保存时不检查
+    pub fn file_hash(&self) -> Option<u64> {
+        self.file_hash
+    }
+
+    pub fn set_file_hash(&mut self, hash: Option<u64>) {
+        self.file_hash = hash;
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved = self.revision;
+    }
+
+    /// 即使没有修改也需要在下一次保存时写入，例如替换无法解析的数据文件
+    pub fn mark_modified(&mut self) {
+        self.revision += 1;
     }
 
     pub fn push(&mut self, dir: LinkDir) -> Result<(), Error> {
-        if self.set.contains(dir.identifier()) {
+        if self.index.contains_key(dir.identifier()) {
+            let identifier = dir.identifier().to_string();
             return Err(Error::new(
-                ErrorKind::DuplicatedLinkDirIdentifier(dir),
+                ErrorKind::DuplicatedLinkDirIdentifier(Box::new(dir)),
                 "Directory name already exists",
-            ));
+            )
+            .with_subject(identifier));
         }
-        self.set.insert(dir.identifier().to_string());
+        self.index
+            .insert(dir.identifier().to_string(), self.map.len());
         self.map.push(dir);
+        self.revision += 1;
         Ok(())
     }
 
@@ -43,29 +84,110 @@ impl LinkDirSet {
         }
 
         let item = self.map.remove(index);
-        self.set.remove(item.identifier());
+        self.index.remove(item.identifier());
+        // 删除之后`index`及之后的文件夹位置都改变了
+        for (idx, dir) in self.map.iter().enumerate().skip(index) {
+            if let Some(pos) = self.index.get_mut(dir.identifier()) {
+                *pos = idx;
+            }
+        }
+        self.revision += 1;
         item
     }
 
     pub fn rename(&mut self, idx: usize, identifier: &str) -> Result<(), Error> {
-        if self.set.contains(identifier) {
+        if self.index.contains_key(identifier) {
             return Err(Error::new(
                 ErrorKind::DuplicatedIdentifier,
                 "Directory name already exists",
-            ));
+            )
+            .with_subject(identifier));
         }
+        // 先修改标识符，新的标识符无效时集合保持不变
         let dir = &mut self.map[idx];
-        self.set.remove(dir.identifier());
+        let old = dir.identifier().to_string();
         dir.set_identifier(identifier)?;
-        self.set.insert(identifier.to_string());
+        self.index.remove(&old);
+        self.index.insert(identifier.to_string(), idx);
+        self.revision += 1;
         Ok(())
     }
 
     pub fn swap(&mut self, a: usize, b: usize) {
         self.map.swap(a, b);
+        self.index.insert(self.map[a].identifier().to_string(), a);
+        self.index.insert(self.map[b].identifier().to_string(), b);
+        self.revision += 1;
+    }
+
+    /// 修改第`idx`个文件夹中的链接，文件夹的标识符只能通过`rename`修改
+    pub fn dir_mut(&mut self, idx: usize) -> LinkDirMut<'_> {
+        LinkDirMut {
+            dir: &mut self.map[idx],
+            revision: &mut self.revision,
+        }
+    }
+
+    /// 根据`folder/link`查找链接，标识符本身可能包含`/`，因此会尝试每一个分隔位置
+    pub fn find_link(&self, target: &str) -> Option<&Link> {
+        target.match_indices('/').find_map(|(idx, _)| {
+            let (folder, link) = (&target[..idx], &target[idx + 1..]);
+            self.find(folder)?.find(link)
+        })
+    }
+
+    /// 合并只读的一层数据，个人数据和先合并的层优先
+    pub fn merge_layer(&mut self, layer: LinkDirSet, origin: Origin) {
+        for mut dir in layer.map {
+            match self.index_of(dir.identifier()) {
+                Some(idx) => self.map[idx].merge_layer(dir, origin),
+                None => {
+                    dir.set_origin(origin);
+                    self.index
+                        .insert(dir.identifier().to_string(), self.map.len());
+                    self.map.push(dir);
+                }
+            }
+        }
+    }
+
+    /// 去掉共享数据之后的个人数据，保存时只写入这一部分
+    pub fn personal(&self) -> LinkDirSet {
+        let mut ret = Self::new();
+        for dir in self.map.iter().filter_map(LinkDir::personal) {
+            // 标识符在原来的集合中已经不重复
+            let _ = ret.push(dir);
+        }
+        ret.mark_saved();
+        ret
+    }
+
+    /// 所有链接的`folder/link`名称
+    pub fn link_names(&self) -> impl Iterator<Item = String> + '_ {
+        self.map.iter().flat_map(|dir| {
+            dir.iter()
+                .map(move |link| format!("{}/{}", dir.identifier(), link.identifier()))
+        })
+    }
+
+    /// `index`与文件夹的标识符和位置一一对应，并且每个文件夹也满足`LinkDir::is_consistent`
+    pub fn is_consistent(&self) -> bool {
+        self.index.len() == self.map.len()
+            && self.map.iter().enumerate().all(|(idx, dir)| {
+                dir.is_consistent() && self.index_of(dir.identifier()) == Some(idx)
+            })
     }
 }
 
+// 修改次数不影响数据本身是否相同
+impl PartialEq for LinkDirSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl Eq for LinkDirSet {}
+
 impl Deref for LinkDirSet {
     type Target = [LinkDir];
     fn deref(&self) -> &Self::Target {
@@ -73,18 +195,77 @@ impl Deref for LinkDirSet {
     }
 }
 
-impl DerefMut for LinkDirSet {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        self.map.as_mut_slice()
+/// 只能进行不破坏`LinkDirSet`和`LinkDir`中标识符索引的修改，修改成功时记录一次修改
+#[derive(Debug)]
+pub struct LinkDirMut<'a> {
+    dir: &'a mut LinkDir,
+    revision: &'a mut u64,
+}
+
+impl LinkDirMut<'_> {
+    fn modified<T>(&mut self, result: Result<T, Error>) -> Result<T, Error> {
+        if result.is_ok() {
+            *self.revision += 1;
+        }
+        result
+    }
+
+    pub fn push(&mut self, link: Link) -> Result<(), Error> {
+        let result = self.dir.push(link);
+        self.modified(result)
+    }
+
+    pub fn remove(&mut self, index: usize) -> Link {
+        *self.revision += 1;
+        self.dir.remove(index)
+    }
+
+    pub fn rename(&mut self, idx: usize, identifier: &str) -> Result<(), Error> {
+        let result = self.dir.rename(idx, identifier);
+        self.modified(result)
+    }
+
+    pub fn relink(&mut self, idx: usize, path: &Path) -> Result<(), Error> {
+        let result = self.dir.relink(idx, path);
+        self.modified(result)
+    }
+
+    pub fn swap(&mut self, a: usize, b: usize) {
+        *self.revision += 1;
+        self.dir.swap(a, b);
+    }
+
+    pub fn set_links(&mut self, links: Vec<Link>) -> Result<(), Error> {
+        let result = self.dir.set_links(links);
+        self.modified(result)
+    }
+
+    pub fn fork(&mut self) {
+        *self.revision += 1;
+        self.dir.fork();
+    }
+
+    pub fn fork_link(&mut self, idx: usize) {
+        *self.revision += 1;
+        self.dir.fork_link(idx);
+    }
+}
+
+impl Deref for LinkDirMut<'_> {
+    type Target = LinkDir;
+    fn deref(&self) -> &Self::Target {
+        self.dir
     }
 }
 
+#[cfg(feature = "serde-json")]
 impl Serialize for LinkDirSet {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         self.map.serialize(serializer)
     }
 }
 
+#[cfg(feature = "serde-json")]
 impl<'de> Deserialize<'de> for LinkDirSet {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         let map = <Vec<LinkDir> as Deserialize<'de>>::deserialize(deserializer)?;
@@ -92,6 +273,8 @@ impl<'de> Deserialize<'de> for LinkDirSet {
         for link_dir in map {
             ret.push(link_dir).map_err(serde::de::Error::custom)?;
         }
+        // 与数据文件中的内容相同
+        ret.mark_saved();
         Ok(ret)
     }
 }
@@ -7,6 +7,8 @@ use super::link::Link;
 pub struct Error {
     kind: ErrorKind,
     message: &'static str,
+    // 出错的标识符或者路径
+    subject: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -14,13 +16,22 @@ pub enum ErrorKind {
     InvaildIdentifier,
     InvaildPath,
     DuplicatedLinkIdentifier(Link),
-    DuplicatedLinkDirIdentifier(LinkDir),
+    DuplicatedLinkDirIdentifier(Box<LinkDir>),
     DuplicatedIdentifier,
 }
 
 impl Error {
     pub fn new(kind: ErrorKind, message: &'static str) -> Self {
-        Self { kind, message }
+        Self {
+            kind,
+            message,
+            subject: None,
+        }
+    }
+
+    pub fn with_subject<S: Into<String>>(mut self, subject: S) -> Self {
+        self.subject = Some(subject.into());
+        self
     }
 
     pub fn kind(&self) -> &ErrorKind {
@@ -30,6 +41,10 @@ impl Error {
     pub fn message(&self) -> &str {
         self.message
     }
+
+    pub fn subject(&self) -> Option<&str> {
+        self.subject.as_deref()
+    }
 }
 
 impl ErrorKind {
@@ -46,7 +61,10 @@ impl ErrorKind {
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {}", self.kind, self.message)
+        match &self.subject {
+            Some(subject) => write!(f, "{}: `{subject}`", self.message),
+            None => f.write_str(self.message),
+        }
     }
 }
 
@@ -0,0 +1,102 @@
+use std::{
+    ffi::{OsStr, OsString},
+    fmt::Write,
+};
+
+/// 把路径转换为可以显示和编辑的文本，不会丢失任何字节
+///
+/// `\`写作`\\`，不是UTF-8的字节写作`\xNN`，其余字符保持不变
+pub fn escape(path: &OsStr) -> String {
+    let mut escaped = String::new();
+    for chunk in path.as_encoded_bytes().utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '\\' => escaped.push_str(r"\\"),
+                c => escaped.push(c),
+            }
+        }
+        for byte in chunk.invalid() {
+            let _ = write!(escaped, r"\x{byte:02X}");
+        }
+    }
+    escaped
+}
+
+/// `escape`的逆操作，无法识别的`\`按原样保留
+pub fn unescape(text: &str) -> OsString {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find('\\') {
+        bytes.extend_from_slice(&rest.as_bytes()[..idx]);
+        let after = &rest[idx + 1..];
+        if let Some(after) = after.strip_prefix('\\') {
+            bytes.push(b'\\');
+            rest = after;
+        } else if let Some(byte) = after
+            .strip_prefix('x')
+            .and_then(|hex| hex.get(..2))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            bytes.push(byte);
+            rest = &after[3..];
+        } else {
+            bytes.push(b'\\');
+            rest = after;
+        }
+    }
+    bytes.extend_from_slice(rest.as_bytes());
+    os_string_from_bytes(bytes)
+}
+
+#[cfg(unix)]
+pub(super) fn os_string_from_bytes(bytes: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes)
+}
+
+// 其他平台的路径不能包含任意字节
+#[cfg(not(unix))]
+pub(super) fn os_string_from_bytes(bytes: Vec<u8>) -> OsString {
+    OsString::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// `Link::path`的JSON格式：UTF-8路径直接保存为字符串，否则保存为`{"encoded": "..."}`
+#[cfg(feature = "serde-json")]
+pub mod path {
+    use std::path::{Path, PathBuf};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::*;
+
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum Repr<'a> {
+        Plain(&'a str),
+        Encoded { encoded: String },
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OwnedRepr {
+        Plain(String),
+        Encoded { encoded: String },
+    }
+
+    pub fn serialize<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+        match path.to_str() {
+            Some(path) => Repr::Plain(path).serialize(serializer),
+            None => Repr::Encoded {
+                encoded: escape(path.as_os_str()),
+            }
+            .serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PathBuf, D::Error> {
+        Ok(match OwnedRepr::deserialize(deserializer)? {
+            OwnedRepr::Plain(path) => PathBuf::from(path),
+            OwnedRepr::Encoded { encoded } => PathBuf::from(unescape(&encoded)),
+        })
+    }
+}
@@ -0,0 +1,40 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use super::dirset::LinkDirSet;
+
+/// 路径到链接的索引，同时按照原始路径和展开后的路径查找
+///
+/// 索引不会随着数据的修改而更新，数据修改后需要重新建立
+#[derive(Debug, Default)]
+pub struct PathIndex {
+    // 值为链接所在文件夹的位置和链接在文件夹中的位置
+    map: HashMap<PathBuf, Vec<(usize, usize)>>,
+}
+
+impl PathIndex {
+    pub fn new(data: &LinkDirSet) -> Self {
+        let mut map: HashMap<PathBuf, Vec<(usize, usize)>> = HashMap::new();
+        for (dir_idx, dir) in data.iter().enumerate() {
+            for (idx, link) in dir.iter().enumerate() {
+                map.entry(link.path().to_path_buf())
+                    .or_default()
+                    .push((dir_idx, idx));
+                // 路径模板展开的结果取决于环境变量和当前目录，建立索引时展开一次
+                if let Ok(resolved) = link.resolve()
+                    && resolved != link.path()
+                {
+                    map.entry(resolved).or_default().push((dir_idx, idx));
+                }
+            }
+        }
+        Self { map }
+    }
+
+    /// 原始路径或者展开后的路径为`path`的链接，按照在数据中的顺序排列
+    pub fn get(&self, path: &Path) -> &[(usize, usize)] {
+        self.map.get(path).map(Vec::as_slice).unwrap_or_default()
+    }
+}
@@ -3,14 +3,24 @@ use std::{
     path::{self, Path, PathBuf},
 };
 
+#[cfg(feature = "serde-json")]
 use serde::{Deserialize, Serialize};
 
 use super::err::{Error, ErrorKind};
+use super::escape;
+use super::origin::Origin;
+use super::template::{self, ExpandError};
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-json", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-json", serde(try_from = "LinkFile"))]
 pub struct Link {
     identifier: String,
+    #[cfg_attr(feature = "serde-json", serde(with = "super::escape::path"))]
     path: PathBuf,
+    // 来源只在加载时确定，不写入数据文件
+    #[cfg_attr(feature = "serde-json", serde(skip))]
+    origin: Origin,
 }
 
 impl Link {
@@ -21,12 +31,14 @@ impl Link {
                 "Link name is empty",
             ));
         }
-        if !path.is_absolute() {
-            return Err(Error::new(ErrorKind::InvaildPath, "path is not absolute"));
+        if !path.is_absolute() && !template::is_template(path) {
+            return Err(Error::new(ErrorKind::InvaildPath, "Path is not absolute")
+                .with_subject(path.display().to_string()));
         }
         Ok(Self {
             identifier: identifier.to_string(),
             path: path.to_path_buf(),
+            origin: Origin::Personal,
         })
     }
 
@@ -49,6 +61,33 @@ impl Link {
         &self.path
     }
 
+    pub fn is_template(&self) -> bool {
+        template::is_template(&self.path)
+    }
+
+    /// 路径相对于当前目录所在的git仓库，不在仓库中时链接不可用
+    pub fn is_repo_relative(&self) -> bool {
+        template::is_repo_relative(&self.path)
+    }
+
+    /// 展开路径模板得到实际的路径，选择链接时使用
+    pub fn resolve(&self) -> Result<PathBuf, ExpandError> {
+        template::expand(&self.path)
+    }
+
+    /// 与`resolve`相同，`:/`和`${GIT_ROOT}`使用`repo`，界面中绘制时使用
+    pub fn resolve_in(&self, repo: Option<&Path>) -> Result<PathBuf, ExpandError> {
+        template::expand_in(&self.path, repo)
+    }
+
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    pub fn set_origin(&mut self, origin: Origin) {
+        self.origin = origin;
+    }
+
     pub fn change_identifer(&mut self, identifier: &str) -> Result<(), Error> {
         if identifier.is_empty() {
             return Err(Error::new(
@@ -61,18 +100,88 @@ impl Link {
     }
 
     pub fn change_path(&mut self, path: &Path) -> Result<(), Error> {
-        if !path.is_absolute() {
-            return Err(Error::new(ErrorKind::InvaildPath, "path is not absolute"));
+        if !path.is_absolute() && !template::is_template(path) {
+            return Err(Error::new(ErrorKind::InvaildPath, "Path is not absolute")
+                .with_subject(path.display().to_string()));
         }
         self.path = path.to_path_buf();
         Ok(())
     }
 }
 
+/// 数据文件中的链接，读取后与`Link::builder`一样检查标识符和路径
+#[cfg(feature = "serde-json")]
+#[derive(Deserialize)]
+struct LinkFile {
+    identifier: String,
+    #[serde(with = "super::escape::path")]
+    path: PathBuf,
+}
+
+#[cfg(feature = "serde-json")]
+impl TryFrom<LinkFile> for Link {
+    type Error = Error;
+
+    fn try_from(link: LinkFile) -> Result<Self, Error> {
+        Link::builder(&link.identifier, &link.path)
+    }
+}
+
+/// 输入框中的路径是转义后的文本，路径模板原样保存，其余的路径转换为绝对路径
 pub fn get_vaild_path(input: &str) -> io::Result<PathBuf> {
-    if input.is_empty() {
+    let input = PathBuf::from(escape::unescape(input));
+    if template::is_template(&input) {
+        Ok(input)
+    } else if input.as_os_str().is_empty() {
         Ok(path::absolute(env::current_dir()?)?)
     } else {
         Ok(path::absolute(input)?)
     }
 }
+
+/// 清理粘贴进路径输入框的文本
+///
+/// 去掉末尾的换行，多行时只保留第一行，去掉成对的引号，
+/// 并把文件管理器复制的`file://`URI转换为普通路径，URI中不是UTF-8的字节原样保留
+pub fn clean_pasted_path(text: &str) -> PathBuf {
+    let line = text.lines().next().unwrap_or_default().trim();
+    let line = ['\'', '"']
+        .into_iter()
+        .find_map(|quote| {
+            line.strip_prefix(quote)
+                .and_then(|line| line.strip_suffix(quote))
+        })
+        .unwrap_or(line);
+
+    match line.strip_prefix("file://") {
+        Some(uri) => {
+            // file:///path 或者 file://localhost/path
+            let path = match uri.find('/') {
+                Some(idx) if uri[..idx].is_empty() || uri[..idx] == *"localhost" => &uri[idx..],
+                _ => return PathBuf::from(line),
+            };
+            percent_decode(path)
+        }
+        None => PathBuf::from(line),
+    }
+}
+
+fn percent_decode(input: &str) -> PathBuf {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%'
+            && let Some(byte) = input
+                .get(idx + 1..idx + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            idx += 3;
+        } else {
+            decoded.push(bytes[idx]);
+            idx += 1;
+        }
+    }
+    PathBuf::from(escape::os_string_from_bytes(decoded))
+}
@@ -0,0 +1,24 @@
+/// 数据来自哪一层，只有个人数据可以修改，也只有个人数据会被保存
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Origin {
+    #[default]
+    Personal,
+    /// 从当前目录向上找到的`.dirlink.json`
+    Project,
+    /// 通过`--team`或`DIR_LINK_TEAM`指定的团队文件
+    Team,
+}
+
+impl Origin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Origin::Personal => "personal",
+            Origin::Project => "project",
+            Origin::Team => "team",
+        }
+    }
+
+    pub fn is_personal(&self) -> bool {
+        *self == Origin::Personal
+    }
+}
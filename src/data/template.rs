@@ -0,0 +1,282 @@
+use std::{
+    env,
+    ffi::OsString,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+/// 内置的变量，没有设置同名的环境变量时使用当前目录所在的git仓库
+pub const GIT_ROOT: &str = "GIT_ROOT";
+
+/// 相对于当前目录所在git仓库根目录的链接，如`:/docs`
+pub const REPO_PREFIX: &str = ":/";
+
+/// 展开路径模板时的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpandError {
+    NoHome,
+    Unset(String),
+    NotInRepo,
+    Unclosed,
+    NotAbsolute(PathBuf),
+}
+
+impl Display for ExpandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpandError::NoHome => write!(f, "home directory not found"),
+            ExpandError::Unset(name) => write!(f, "environment variable `{name}` is not set"),
+            ExpandError::NotInRepo => write!(f, "current directory is not in a git repository"),
+            ExpandError::Unclosed => write!(f, "missing `}}` after `${{`"),
+            ExpandError::NotAbsolute(path) => {
+                write!(f, "`{}` is not an absolute path", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExpandError {}
+
+/// 以`~`、`:/`开头或者包含`${`的路径是模板，在选择时才会展开
+pub fn is_template(path: &Path) -> bool {
+    is_repo_relative(path)
+        || path
+            .to_str()
+            .is_some_and(|path| path == "~" || path.starts_with("~/") || path.contains("${"))
+}
+
+pub fn is_repo_relative(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|path| path.starts_with(REPO_PREFIX))
+}
+
+/// 展开的结果可能与当前目录所在的仓库有关，如`:/docs`和`${GIT_ROOT}/docs`
+pub fn uses_git_root(path: &Path) -> bool {
+    is_repo_relative(path)
+        || path
+            .to_str()
+            .is_some_and(|path| path.contains(&format!("${{{GIT_ROOT}}}")))
+}
+
+/// 展开`~/`和`${VAR}`，不是模板的路径原样返回
+pub fn expand(path: &Path) -> Result<PathBuf, ExpandError> {
+    expand_with(path, &Lookup::system(&current_git_root))
+}
+
+/// 与`expand`相同，但是使用事先查找的仓库根目录，不再访问文件系统
+pub fn expand_in(path: &Path, repo: Option<&Path>) -> Result<PathBuf, ExpandError> {
+    expand_with(path, &Lookup::system(&|| repo.map(Path::to_path_buf)))
+}
+
+/// 展开时读取的环境变量、主目录和仓库根目录
+struct Lookup<'a> {
+    var: &'a dyn Fn(&str) -> Option<OsString>,
+    home: &'a dyn Fn() -> Option<PathBuf>,
+    repo: &'a dyn Fn() -> Option<PathBuf>,
+}
+
+impl<'a> Lookup<'a> {
+    fn system(repo: &'a dyn Fn() -> Option<PathBuf>) -> Self {
+        Self {
+            var: &|name| env::var_os(name),
+            home: &home_dir,
+            repo,
+        }
+    }
+
+    fn var(&self, name: &str) -> Result<OsString, ExpandError> {
+        match (self.var)(name) {
+            Some(value) => Ok(value),
+            None if name == GIT_ROOT => (self.repo)()
+                .map(PathBuf::into_os_string)
+                .ok_or(ExpandError::NotInRepo),
+            None => Err(ExpandError::Unset(name.to_string())),
+        }
+    }
+}
+
+fn expand_with(path: &Path, lookup: &Lookup) -> Result<PathBuf, ExpandError> {
+    let Some(template) = path.to_str().filter(|_| is_template(path)) else {
+        return Ok(path.to_path_buf());
+    };
+
+    let mut expanded = OsString::new();
+    let mut rest = template;
+    if let Some(after) = rest.strip_prefix('~')
+        && (after.is_empty() || after.starts_with('/'))
+    {
+        expanded.push((lookup.home)().ok_or(ExpandError::NoHome)?);
+        rest = after;
+    } else if rest.starts_with(REPO_PREFIX) {
+        // 保留`/`，`:/`总是展开为当前目录所在仓库的根目录，不受`GIT_ROOT`环境变量影响
+        expanded.push((lookup.repo)().ok_or(ExpandError::NotInRepo)?);
+        rest = &rest[1..];
+    }
+    while let Some(start) = rest.find("${") {
+        expanded.push(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find('}').ok_or(ExpandError::Unclosed)?;
+        expanded.push(lookup.var(&after[..end])?);
+        rest = &after[end + 1..];
+    }
+    expanded.push(rest);
+
+    let expanded = PathBuf::from(expanded);
+    if expanded.is_absolute() {
+        Ok(expanded)
+    } else {
+        Err(ExpandError::NotAbsolute(expanded))
+    }
+}
+
+/// 当前目录所在的git仓库
+pub fn current_git_root() -> Option<PathBuf> {
+    env::current_dir().ok().and_then(|dir| git_root(&dir))
+}
+
+/// 从`dir`开始向上查找包含`.git`的目录，`.git`在worktree中是文件
+pub fn git_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::home_dir().filter(|home| home.is_absolute())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::temp::PrivateDir;
+
+    fn vars(name: &str) -> Option<OsString> {
+        match name {
+            "WORKSPACE" => Some(OsString::from("/work")),
+            "REL" => Some(OsString::from("relative")),
+            _ => None,
+        }
+    }
+
+    fn lookup<'a>(home: &'a dyn Fn() -> Option<PathBuf>) -> Lookup<'a> {
+        Lookup {
+            var: &vars,
+            home,
+            repo: &|| Some(PathBuf::from("/repo")),
+        }
+    }
+
+    fn expand(path: &str) -> Result<PathBuf, ExpandError> {
+        expand_with(
+            Path::new(path),
+            &lookup(&|| Some(PathBuf::from("/home/me"))),
+        )
+    }
+
+    #[test]
+    fn expands_home() {
+        assert_eq!(expand("~"), Ok(PathBuf::from("/home/me")));
+        assert_eq!(expand("~/src/foo"), Ok(PathBuf::from("/home/me/src/foo")));
+        // 其他用户的主目录不是模板，原样保留
+        assert!(!is_template(Path::new("~user/src")));
+        assert_eq!(expand("~user/src"), Ok(PathBuf::from("~user/src")));
+    }
+
+    #[test]
+    fn expands_variables() {
+        assert_eq!(expand("${WORKSPACE}/api"), Ok(PathBuf::from("/work/api")));
+        assert_eq!(
+            expand("/srv/${WORKSPACE}/${WORKSPACE}"),
+            Ok(PathBuf::from("/srv//work//work"))
+        );
+        assert_eq!(expand("${GIT_ROOT}/docs"), Ok(PathBuf::from("/repo/docs")));
+        assert_eq!(expand("/plain/path"), Ok(PathBuf::from("/plain/path")));
+    }
+
+    #[test]
+    fn repo_links_use_enclosing_repository() {
+        assert_eq!(expand(":/docs"), Ok(PathBuf::from("/repo/docs")));
+        let env_root = |name: &str| match name {
+            GIT_ROOT => Some(OsString::from("/elsewhere")),
+            _ => None,
+        };
+        let lookup = Lookup {
+            var: &env_root,
+            home: &|| None,
+            repo: &|| Some(PathBuf::from("/repo")),
+        };
+        // 环境变量只影响明确写出的`${GIT_ROOT}`
+        assert_eq!(
+            expand_with(Path::new(":/docs"), &lookup),
+            Ok(PathBuf::from("/repo/docs"))
+        );
+        assert_eq!(
+            expand_with(Path::new("${GIT_ROOT}/docs"), &lookup),
+            Ok(PathBuf::from("/elsewhere/docs"))
+        );
+        let outside = Lookup {
+            var: &env_root,
+            home: &|| None,
+            repo: &|| None,
+        };
+        assert_eq!(
+            expand_with(Path::new(":/docs"), &outside),
+            Err(ExpandError::NotInRepo)
+        );
+    }
+
+    #[test]
+    fn git_root_walks_up_to_dot_git() {
+        let temp = PrivateDir::new("git-root").unwrap();
+        let repo = temp.path().join("repo");
+        let nested = repo.join("crates/core/src");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(git_root(&nested), Some(repo.clone()));
+        assert_eq!(git_root(&repo), Some(repo.clone()));
+
+        // worktree中的`.git`是指向主仓库的文件
+        let worktree = temp.path().join("worktree");
+        fs::create_dir_all(worktree.join("docs")).unwrap();
+        fs::write(worktree.join(".git"), "gitdir: ../repo/.git/worktrees/wt\n").unwrap();
+        assert_eq!(git_root(&worktree.join("docs")), Some(worktree));
+
+        let outside = temp.path().join("outside");
+        fs::create_dir_all(&outside).unwrap();
+        assert_eq!(git_root(&outside), None);
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert_eq!(
+            expand("${MISSING}/api"),
+            Err(ExpandError::Unset("MISSING".to_string()))
+        );
+        assert_eq!(expand("${WORKSPACE/api"), Err(ExpandError::Unclosed));
+        assert_eq!(
+            expand("${REL}/api"),
+            Err(ExpandError::NotAbsolute(PathBuf::from("relative/api")))
+        );
+        let no_home = lookup(&|| None);
+        assert_eq!(
+            expand_with(Path::new("~/src"), &no_home),
+            Err(ExpandError::NoHome)
+        );
+        // 不需要主目录的模板不受影响
+        assert_eq!(
+            expand_with(Path::new("${WORKSPACE}"), &no_home),
+            Ok(PathBuf::from("/work"))
+        );
+    }
+
+    #[test]
+    fn error_messages() {
+        assert_eq!(
+            ExpandError::Unset("WORKSPACE".to_string()).to_string(),
+            "environment variable `WORKSPACE` is not set"
+        );
+        assert_eq!(ExpandError::Unclosed.to_string(), "missing `}` after `${`");
+    }
+}
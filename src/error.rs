@@ -0,0 +1,158 @@
+use std::{fmt::Display, io, path::PathBuf};
+
+use crate::data::{err, origin::Origin, template::ExpandError};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// 退出码，shell脚本可以依此区分失败的原因
+pub const EXIT_OK: u8 = 0;
+/// 没有选择任何链接就退出
+pub const EXIT_CANCELLED: u8 = 1;
+/// 命令行参数错误，与clap一致
+pub const EXIT_USAGE: u8 = 2;
+/// 找不到指定的文件夹或者链接
+pub const EXIT_NOT_FOUND: u8 = 3;
+/// 数据文件无法解析
+pub const EXIT_CORRUPT_DATA: u8 = 4;
+/// 读写文件、终端或者执行外部命令失败
+pub const EXIT_IO: u8 = 5;
+/// 名称、路径或者路径模板无效
+pub const EXIT_INVALID: u8 = 6;
+
+#[derive(Debug)]
+pub enum Error {
+    Cancelled,
+    /// `kind`为`folder`、`link`等，`name`为查找时使用的名称
+    NotFound {
+        kind: &'static str,
+        name: String,
+    },
+    #[cfg(feature = "serde-json")]
+    CorruptData {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    /// `context`说明失败时正在做什么，通常包含路径
+    Io {
+        context: String,
+        source: io::Error,
+    },
+    Invalid(err::Error),
+    InvalidProfile(String),
+    /// 共享的文件夹或者链接不能修改
+    ReadOnly {
+        name: String,
+        origin: Origin,
+    },
+    Unresolved {
+        path: PathBuf,
+        source: ExpandError,
+    },
+    /// 读取之后数据文件被其他进程修改过，保存时不会覆盖
+    Conflict {
+        path: PathBuf,
+    },
+    /// 等待数据文件的锁超时
+    Locked {
+        path: PathBuf,
+    },
+    /// 操作只能写入结果文件，没有传入结果文件时无法交给shell执行
+    NeedsResultFile(&'static str),
+}
+
+impl Error {
+    pub fn not_found<S: Into<String>>(kind: &'static str, name: S) -> Self {
+        Self::NotFound {
+            kind,
+            name: name.into(),
+        }
+    }
+
+    pub fn io<C: Into<String>>(context: C, source: io::Error) -> Self {
+        Self::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Cancelled => EXIT_CANCELLED,
+            Error::NeedsResultFile(_) => EXIT_USAGE,
+            Error::NotFound { .. } => EXIT_NOT_FOUND,
+            #[cfg(feature = "serde-json")]
+            Error::CorruptData { .. } => EXIT_CORRUPT_DATA,
+            Error::Io { .. } | Error::Conflict { .. } | Error::Locked { .. } => EXIT_IO,
+            Error::Invalid(_)
+            | Error::InvalidProfile(_)
+            | Error::ReadOnly { .. }
+            | Error::Unresolved { .. } => EXIT_INVALID,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Cancelled => f.write_str("cancelled"),
+            Error::NotFound { kind, name } => write!(f, "{kind} `{name}` not found"),
+            #[cfg(feature = "serde-json")]
+            Error::CorruptData { path, source } => {
+                write!(f, "corrupt data in `{}`: {source}", path.display())
+            }
+            Error::Io { context, source } => write!(f, "{context}: {source}"),
+            Error::Invalid(err) => write!(f, "{err}"),
+            Error::InvalidProfile(name) => write!(f, "invalid profile name `{name}`"),
+            Error::ReadOnly { name, origin } => write!(
+                f,
+                "`{name}` comes from the {} links and is read-only",
+                origin.as_str()
+            ),
+            Error::Unresolved { path, source } => {
+                write!(f, "cannot resolve `{}`: {source}", path.display())
+            }
+            Error::Conflict { path } => write!(
+                f,
+                "`{}` was changed by another process since it was read, not saved",
+                path.display()
+            ),
+            Error::Locked { path } => {
+                write!(f, "`{}` is locked by another process", path.display())
+            }
+            Error::NeedsResultFile(action) => write!(
+                f,
+                "`{action}` needs a result file, use the shell integration from `dir_link init`"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "serde-json")]
+            Error::CorruptData { source, .. } => Some(source),
+            Error::Io { source, .. } => Some(source),
+            Error::Invalid(err) => Some(err),
+            Error::Unresolved { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<err::Error> for Error {
+    fn from(err: err::Error) -> Self {
+        Self::Invalid(err)
+    }
+}
+
+/// 为`io::Error`加上说明，转换为[`Error::Io`]
+pub trait Context<T> {
+    fn context<C: Into<String>>(self, context: C) -> Result<T>;
+}
+
+impl<T> Context<T> for io::Result<T> {
+    fn context<C: Into<String>>(self, context: C) -> Result<T> {
+        self.map_err(|err| Error::io(context, err))
+    }
+}
@@ -0,0 +1,229 @@
+use std::{collections::HashSet, fmt::Write, path::Path};
+
+use crate::data::{dir::LinkDir, dirset::LinkDirSet, link::Link, template};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Format {
+    /// zsh的命名目录`hash -d name=path`
+    Hash,
+    /// bash的`alias name='cd path'`
+    Bash,
+    /// zsh的`alias name='cd path'`
+    Zsh,
+    /// fish的`alias name 'cd path'`
+    Fish,
+    /// GTK的`~/.config/gtk-3.0/bookmarks`
+    Gtk,
+    /// 可以直接赋值给`CDPATH`的路径列表
+    Cdpath,
+    /// `export DL_<folder>_<link>=path`
+    Env,
+}
+
+/// 导出的文本以及被跳过的链接
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Exported {
+    pub text: String,
+    pub skipped: Vec<Skipped>,
+}
+
+/// 无法展开或者无法在该格式中表示的链接
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    /// `folder/link`
+    pub link: String,
+    pub reason: String,
+}
+
+impl Skipped {
+    fn new(dir: &LinkDir, link: &Link, reason: impl Into<String>) -> Self {
+        Self {
+            link: format!("{}/{}", dir.identifier(), link.identifier()),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// 把`data`导出为`format`格式的文本，`folder`不为`None`时只导出该文件夹
+///
+/// 导出全部文件夹时名称为`<folder>_<link>`，只导出一个文件夹时名称为链接的标识符，
+/// 名称中不能在shell中使用的字符会被替换为`_`。
+/// 不是UTF-8的路径在shell格式中按字节转义，`CDPATH`中无法表示，会被跳过
+pub fn export(format: Format, data: &LinkDirSet, folder: Option<&str>) -> Option<Exported> {
+    let dirs: Vec<&LinkDir> = match folder {
+        Some(folder) => vec![data.find(folder)?],
+        None => data.iter().collect(),
+    };
+    // 导出时展开路径模板，无法展开的链接不会导出。
+    // 仓库中的链接随当前目录变化，展开后只对导出时所在的仓库有效
+    let mut skipped = Vec::new();
+    let mut entries = Vec::new();
+    for (name, dir, link) in named_links(&dirs, folder.is_none()) {
+        if template::uses_git_root(link.path()) {
+            skipped.push(Skipped::new(
+                dir,
+                link,
+                "path depends on the current git repository",
+            ));
+            continue;
+        }
+        match link.resolve() {
+            Ok(path) => entries.push((name, dir, link, path)),
+            Err(err) => skipped.push(Skipped::new(dir, link, err.to_string())),
+        }
+    }
+
+    let mut output = String::new();
+    match format {
+        Format::Hash => entries.iter().for_each(|(name, _, _, path)| {
+            let _ = writeln!(output, "hash -d {name}={}", sh_quote(path));
+        }),
+        Format::Bash | Format::Zsh => entries.iter().for_each(|(name, _, _, path)| {
+            let command = format!("cd {}", sh_quote(path));
+            let _ = writeln!(output, "alias {name}={}", sh_quote_str(&command));
+        }),
+        Format::Fish => entries.iter().for_each(|(name, _, _, path)| {
+            let command = format!("cd {}", fish_quote(path));
+            let _ = writeln!(output, "alias {name} {}", fish_quote_str(&command));
+        }),
+        Format::Gtk => entries.iter().for_each(|(_, _, link, path)| {
+            let _ = writeln!(
+                output,
+                "file://{} {}",
+                percent_encode(path),
+                link.identifier()
+            );
+        }),
+        Format::Cdpath => {
+            let mut seen = HashSet::new();
+            let mut paths = Vec::new();
+            for (_, dir, link, path) in &entries {
+                match path.to_str() {
+                    // `:`是CDPATH的分隔符
+                    Some(path) if path.contains(':') => {
+                        skipped.push(Skipped::new(dir, link, "path contains `:`"));
+                    }
+                    Some(path) if seen.insert(path) => paths.push(path),
+                    Some(_) => {}
+                    None => skipped.push(Skipped::new(dir, link, "path is not valid UTF-8")),
+                }
+            }
+            let _ = writeln!(output, "{}", paths.join(":"));
+        }
+        Format::Env => entries.iter().for_each(|(name, _, _, path)| {
+            let _ = writeln!(output, "export DL_{name}={}", sh_quote(path));
+        }),
+    }
+    Some(Exported {
+        text: output,
+        skipped,
+    })
+}
+
+/// 为每个链接生成在shell中可用且不重复的名称
+fn named_links<'a>(
+    dirs: &[&'a LinkDir],
+    with_folder: bool,
+) -> Vec<(String, &'a LinkDir, &'a Link)> {
+    let mut used = HashSet::new();
+    let mut entries = Vec::new();
+    for dir in dirs {
+        for link in dir.iter() {
+            let name = if with_folder {
+                format!(
+                    "{}_{}",
+                    shell_name(dir.identifier()),
+                    shell_name(link.identifier())
+                )
+            } else {
+                shell_name(link.identifier())
+            };
+            let name = if used.contains(&name) {
+                (2..)
+                    .map(|n| format!("{name}_{n}"))
+                    .find(|name| !used.contains(name))
+                    .unwrap()
+            } else {
+                name
+            };
+            used.insert(name.clone());
+            entries.push((name, *dir, link));
+        }
+    }
+    entries
+}
+
+fn shell_name(identifier: &str) -> String {
+    let name: String = identifier
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    // 不能以数字开头
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+fn sh_quote(path: &Path) -> String {
+    match path.to_str() {
+        Some(path) => sh_quote_str(path),
+        None => ansi_c_quote(path.as_os_str().as_encoded_bytes()),
+    }
+}
+
+/// bash和zsh的`$'...'`，单引号中无法表示任意字节
+fn ansi_c_quote(bytes: &[u8]) -> String {
+    let mut quoted = String::from("$'");
+    for &byte in bytes {
+        if (byte.is_ascii_graphic() || byte == b' ') && !matches!(byte, b'\'' | b'\\') {
+            quoted.push(byte as char);
+        } else {
+            let _ = write!(quoted, "\\x{byte:02x}");
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// 使用单引号，其中的`'`替换为`'\''`
+fn sh_quote_str(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+fn fish_quote(path: &Path) -> String {
+    match path.to_str() {
+        Some(path) => fish_quote_str(path),
+        // 引号外的`\XHH`表示一个字节
+        None => {
+            let mut quoted = String::new();
+            for &byte in path.as_os_str().as_encoded_bytes() {
+                if byte.is_ascii_alphanumeric() || b"/-_.".contains(&byte) {
+                    quoted.push(byte as char);
+                } else {
+                    let _ = write!(quoted, "\\X{byte:02x}");
+                }
+            }
+            quoted
+        }
+    }
+}
+
+/// fish的单引号中只有`\\`和`\'`需要转义
+fn fish_quote_str(text: &str) -> String {
+    format!("'{}'", text.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+fn percent_encode(path: &Path) -> String {
+    let mut encoded = String::new();
+    for &byte in path.as_os_str().as_encoded_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
@@ -0,0 +1,401 @@
+use std::{
+    collections::HashSet,
+    env, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use directories::BaseDirs;
+
+use crate::data::{dir::LinkDir, dirset::LinkDirSet, err::Error, link::Link};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Source {
+    Zoxide,
+    Autojump,
+    Fasd,
+    Ranger,
+    Lf,
+    Nnn,
+    Gtk,
+    /// zsh的命名目录`hash -d name=path`
+    Zsh,
+}
+
+impl Source {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Source::Zoxide => "zoxide",
+            Source::Autojump => "autojump",
+            Source::Fasd => "fasd",
+            Source::Ranger => "ranger",
+            Source::Lf => "lf",
+            Source::Nnn => "nnn",
+            Source::Gtk => "gtk",
+            Source::Zsh => "zsh",
+        }
+    }
+
+    /// 该工具默认保存书签的文件，zoxide的数据库是二进制格式，nnn的书签保存在环境变量或者目录中
+    pub fn default_path(&self) -> Option<PathBuf> {
+        let base = BaseDirs::new()?;
+        let path = match self {
+            Source::Zoxide => return None,
+            Source::Autojump => base.data_local_dir().join("autojump/autojump.txt"),
+            Source::Fasd => env::var_os("_FASD_DATA")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| base.home_dir().join(".fasd")),
+            Source::Ranger => base.data_local_dir().join("ranger/bookmarks"),
+            Source::Lf => base.data_local_dir().join("lf/marks"),
+            Source::Nnn => base.config_dir().join("nnn/bookmarks"),
+            Source::Gtk => base.config_dir().join("gtk-3.0/bookmarks"),
+            Source::Zsh => env::var_os("ZDOTDIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| base.home_dir().to_path_buf())
+                .join(".zshrc"),
+        };
+        Some(path)
+    }
+
+    pub fn parse(&self, text: &str) -> Vec<Entry> {
+        match self {
+            Source::Zoxide => parse_zoxide(text),
+            Source::Autojump => parse_autojump(text),
+            Source::Fasd => parse_fasd(text),
+            Source::Ranger | Source::Lf => parse_marks(text),
+            Source::Nnn => parse_nnn(text),
+            Source::Gtk => parse_gtk(text),
+            Source::Zsh => parse_zsh_hash(text),
+        }
+    }
+}
+
+/// 从其他工具中读到的一条书签，`name`为`None`时使用目录名作为标识符
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: Option<String>,
+    pub path: PathBuf,
+}
+
+impl Entry {
+    fn new(name: Option<&str>, path: &str) -> Self {
+        Self::with_path(name, expand_home(path))
+    }
+
+    fn with_path(name: Option<&str>, path: PathBuf) -> Self {
+        Self {
+            name: name.filter(|name| !name.is_empty()).map(String::from),
+            path,
+        }
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    let home = || BaseDirs::new().map(|base| base.home_dir().to_path_buf());
+    match path.strip_prefix('~') {
+        Some("") => home().unwrap_or_else(|| PathBuf::from(path)),
+        Some(rest) if rest.starts_with('/') => match home() {
+            Some(home) => home.join(&rest[1..]),
+            None => PathBuf::from(path),
+        },
+        _ => PathBuf::from(path),
+    }
+}
+
+/// `zoxide query --list --score`的输出，每行为`score path`
+pub fn parse_zoxide(text: &str) -> Vec<Entry> {
+    text.lines()
+        .filter_map(|line| line.trim_start().split_once(' '))
+        .map(|(_, path)| Entry::new(None, path.trim_start()))
+        .collect()
+}
+
+/// 每行为`weight<TAB>path`，按照权重从高到低排列
+pub fn parse_autojump(text: &str) -> Vec<Entry> {
+    let mut entries: Vec<_> = text
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter_map(|(weight, path)| Some((weight.parse::<f64>().ok()?, path)))
+        .collect();
+    entries.sort_by(|a, b| b.0.total_cmp(&a.0));
+    entries
+        .into_iter()
+        .map(|(_, path)| Entry::new(None, path))
+        .collect()
+}
+
+/// 每行为`path|rank|time`，按照rank从高到低排列
+pub fn parse_fasd(text: &str) -> Vec<Entry> {
+    let mut entries: Vec<_> = text
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.rsplitn(3, '|');
+            let _time = parts.next()?;
+            let rank = parts.next()?.parse::<f64>().ok()?;
+            Some((rank, parts.next()?))
+        })
+        .collect();
+    entries.sort_by(|a, b| b.0.total_cmp(&a.0));
+    entries
+        .into_iter()
+        .map(|(_, path)| Entry::new(None, path))
+        .collect()
+}
+
+/// ranger的bookmarks和lf的marks，每行为`key:path`
+pub fn parse_marks(text: &str) -> Vec<Entry> {
+    text.lines()
+        .filter_map(|line| line.split_once(':'))
+        // ranger会自动保存`'`对应的上一个位置，不是用户的书签
+        .filter(|(key, _)| *key != "'")
+        .map(|(key, path)| Entry::new(Some(key), path))
+        .collect()
+}
+
+/// `NNN_BMS`的格式，`key:path`之间用`;`分隔
+pub fn parse_nnn(text: &str) -> Vec<Entry> {
+    text.split([';', '\n'])
+        .filter_map(|item| item.split_once(':'))
+        .map(|(key, path)| Entry::new(Some(key.trim()), path))
+        .collect()
+}
+
+/// 每行为`file:///path label`，路径经过URI编码
+pub fn parse_gtk(text: &str) -> Vec<Entry> {
+    text.lines()
+        .filter(|line| line.starts_with("file://"))
+        .map(|line| {
+            let (uri, label) = match line.split_once(' ') {
+                Some((uri, label)) => (uri, Some(label)),
+                None => (line, None),
+            };
+            Entry::with_path(label, crate::data::link::clean_pasted_path(uri))
+        })
+        .collect()
+}
+
+/// `.zshrc`中的`hash -d name=path`
+///
+/// 只读取以`hash -d`开头的行，`.zshrc`中其他的`name=value`是变量赋值而不是命名目录
+pub fn parse_zsh_hash(text: &str) -> Vec<Entry> {
+    text.lines()
+        .flat_map(|line| {
+            let words = shell_words(line);
+            match words.split_first_chunk() {
+                Some(([hash, flag], assignments)) if hash == "hash" && flag == "-d" => assignments
+                    .iter()
+                    .filter_map(|item| item.split_once('='))
+                    .map(|(name, path)| Entry::new(Some(name), path))
+                    .collect(),
+                _ => Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// 按照shell的规则把一行分为单词，去掉引号和转义，忽略`#`之后的注释
+fn shell_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '#' if word.is_none() => break,
+            '\\' => word.get_or_insert_default().extend(chars.next()),
+            '\'' => {
+                let word = word.get_or_insert_default();
+                word.extend(chars.by_ref().take_while(|&c| c != '\''));
+            }
+            '"' => {
+                let word = word.get_or_insert_default();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        // 双引号中只有这几个字符可以转义
+                        '\\' => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => word.push('\\'),
+                        },
+                        c => word.push(c),
+                    }
+                }
+            }
+            c => word.get_or_insert_default().push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+/// 读取书签，`from`为`None`时使用工具默认的位置
+pub fn load(source: Source, from: Option<&Path>) -> io::Result<Vec<Entry>> {
+    if let Some(path) = from {
+        return Ok(source.parse(&fs::read_to_string(path)?));
+    }
+    let text = match source {
+        Source::Zoxide => {
+            let output = Command::new("zoxide")
+                .args(["query", "--list", "--score"])
+                .output()?;
+            // 失败时不能当作没有书签，把zoxide的错误信息返回给用户
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(io::Error::other(format!(
+                    "zoxide exited with {}: {}",
+                    output.status,
+                    stderr.trim()
+                )));
+            }
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+        Source::Nnn => match env::var("NNN_BMS") {
+            Ok(bms) => bms,
+            Err(_) => return load_nnn_dir(),
+        },
+        _ => {
+            let path = source.default_path().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "home directory not found")
+            })?;
+            fs::read_to_string(path)?
+        }
+    };
+    Ok(source.parse(&text))
+}
+
+/// nnn也可以把书签保存为`~/.config/nnn/bookmarks`中的符号链接
+fn load_nnn_dir() -> io::Result<Vec<Entry>> {
+    let dir = Source::Nnn
+        .default_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "home directory not found"))?;
+    let mut entries = Vec::new();
+    for item in fs::read_dir(dir)? {
+        let item = item?;
+        if let Ok(target) = fs::read_link(item.path()) {
+            entries.push(Entry {
+                name: Some(item.file_name().to_string_lossy().into_owned()),
+                path: target,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// 在`used`中找到一个不重复的标识符，重复时依次添加`-2`、`-3`等后缀
+fn dedup_identifier(name: &str, used: impl Fn(&str) -> bool) -> String {
+    if !used(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|n| format!("{name}-{n}"))
+        .find(|name| !used(name))
+        .unwrap()
+}
+
+fn entry_identifier(entry: &Entry) -> String {
+    entry
+        .name
+        .clone()
+        .or_else(|| {
+            entry
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| String::from("root"))
+}
+
+/// 转换后的文件夹，以及不是绝对路径等无法转换为链接的书签
+#[derive(Debug)]
+pub struct Imported {
+    pub dir: LinkDir,
+    pub skipped: Vec<Entry>,
+}
+
+/// 把读取到的书签转换为以`folder`命名的文件夹，重复的路径只保留第一个
+pub fn to_link_dir(folder: &str, entries: Vec<Entry>) -> Result<Imported, Error> {
+    let mut dir = LinkDir::builder(folder)?;
+    let mut skipped = Vec::new();
+    let mut paths = HashSet::new();
+    for entry in entries {
+        if !paths.insert(entry.path.clone()) {
+            continue;
+        }
+        let identifier = dedup_identifier(&entry_identifier(&entry), |name| {
+            dir.index_of(name).is_some()
+        });
+        match Link::builder(&identifier, &entry.path) {
+            Ok(link) => dir.push(link).expect("identifier is deduplicated"),
+            Err(_) => skipped.push(entry),
+        }
+    }
+    Ok(Imported { dir, skipped })
+}
+
+/// 合并之前的预览，链接的标识符已经按照目标文件夹去重
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportItem {
+    pub folder: String,
+    pub link: Link,
+    // 目标文件夹不存在，需要新建
+    pub new_folder: bool,
+}
+
+/// 计算把`dirs`合并进`data`的结果：同名文件夹合并，目标文件夹中已有的路径被跳过
+pub fn prepare(data: &LinkDirSet, dirs: Vec<LinkDir>) -> Vec<ImportItem> {
+    let mut items = Vec::new();
+    for dir in dirs {
+        let target = data.find(dir.identifier());
+        let paths: HashSet<&Path> = target
+            .into_iter()
+            .flat_map(|target| target.iter().map(Link::path))
+            .collect();
+        let mut used = HashSet::new();
+        for link in dir.iter() {
+            if paths.contains(link.path()) {
+                continue;
+            }
+            let identifier = dedup_identifier(link.identifier(), |name| {
+                used.contains(name) || target.is_some_and(|target| target.index_of(name).is_some())
+            });
+            used.insert(identifier.clone());
+            let mut link = link.clone();
+            link.set_identifier(&identifier)
+                .expect("identifier of link is not empty");
+            items.push(ImportItem {
+                folder: dir.identifier().to_string(),
+                link,
+                new_folder: target.is_none(),
+            });
+        }
+    }
+    items
+}
+
+/// 应用[`prepare`]的结果，返回成功导入的链接数量
+pub fn merge(data: &mut LinkDirSet, items: Vec<ImportItem>) -> usize {
+    let mut count = 0;
+    for item in items {
+        let idx = match data.index_of(&item.folder) {
+            Some(idx) => idx,
+            None => {
+                let Ok(dir) = LinkDir::builder(&item.folder) else {
+                    continue;
+                };
+                if data.push(dir).is_err() {
+                    continue;
+                }
+                data.len() - 1
+            }
+        };
+        if data.dir_mut(idx).push(item.link).is_ok() {
+            count += 1;
+        }
+    }
+    count
+}
@@ -0,0 +1,31 @@
+/// 可以生成集成脚本的shell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Nushell,
+    Elvish,
+    Xonsh,
+}
+
+impl Shell {
+    fn template(&self) -> &'static str {
+        match self {
+            Shell::Bash => include_str!("../shell/init.bash"),
+            Shell::Zsh => include_str!("../shell/init.zsh"),
+            Shell::Fish => include_str!("../shell/init.fish"),
+            Shell::Nushell => include_str!("../shell/init.nu"),
+            Shell::Elvish => include_str!("../shell/init.elv"),
+            Shell::Xonsh => include_str!("../shell/init.xsh"),
+        }
+    }
+}
+
+/// 生成`shell`的集成脚本，`cmd`为跳转使用的命令名
+///
+/// 脚本定义的命令不带参数时打开TUI，参数为`folder/link`时直接跳转，
+/// 同时绑定Ctrl+G打开TUI，并补全`folder/link`
+pub fn init(shell: Shell, cmd: &str) -> String {
+    shell.template().replace("{{cmd}}", cmd)
+}
@@ -0,0 +1,114 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    data::{dirset::LinkDirSet, origin::Origin},
+    error::Error,
+    store,
+};
+
+/// 项目数据文件名，从当前目录开始向上查找
+pub const PROJECT_FILE: &str = ".dirlink.json";
+/// 团队数据文件列表，格式与`PATH`相同
+pub const TEAM_ENV: &str = "DIR_LINK_TEAM";
+
+/// 加载时合并到个人数据中的只读数据文件
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Layers {
+    project: Option<PathBuf>,
+    team: Vec<PathBuf>,
+}
+
+impl Layers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用`--team`指定的文件和`DIR_LINK_TEAM`中的文件，并从当前目录查找项目文件
+    pub fn discover(team: Vec<PathBuf>) -> Self {
+        let mut layers = Self::new().with_team(team);
+        if let Some(paths) = env::var_os(TEAM_ENV) {
+            layers
+                .team
+                .extend(env::split_paths(&paths).filter(|p| !p.as_os_str().is_empty()));
+        }
+        match env::current_dir() {
+            Ok(dir) => layers.with_project(&dir),
+            Err(_) => layers,
+        }
+    }
+
+    pub fn with_team(mut self, team: Vec<PathBuf>) -> Self {
+        self.team = team;
+        self
+    }
+
+    /// 从`dir`开始向上查找`.dirlink.json`
+    pub fn with_project(mut self, dir: &Path) -> Self {
+        self.project = dir
+            .ancestors()
+            .map(|dir| dir.join(PROJECT_FILE))
+            .find(|path| path.is_file());
+        self
+    }
+
+    pub fn without_project(mut self) -> Self {
+        self.project = None;
+        self
+    }
+
+    /// 把共享文件复制到`dir`中，重放时不再读取当前目录和环境变量中的文件
+    pub fn sandbox(&self, dir: &Path) -> io::Result<Self> {
+        let copy = |path: &Path, name: String| -> io::Result<PathBuf> {
+            // 不存在的共享文件保持原来的路径，合并时同样会显示警告
+            if !path.is_file() {
+                return Ok(path.to_path_buf());
+            }
+            let target = dir.join(name);
+            fs::copy(path, &target)?;
+            Ok(target)
+        };
+        let project = match &self.project {
+            Some(project) => Some(copy(project, String::from("project.json"))?),
+            None => None,
+        };
+        let team = self
+            .team
+            .iter()
+            .enumerate()
+            .map(|(idx, team)| copy(team, format!("team-{idx}.json")))
+            .collect::<io::Result<_>>()?;
+        Ok(Self { project, team })
+    }
+
+    pub fn project(&self) -> Option<&Path> {
+        self.project.as_deref()
+    }
+
+    pub fn team(&self) -> &[PathBuf] {
+        &self.team
+    }
+
+    /// 按项目、团队的顺序合并到`data`中，同名的链接只保留优先级最高的一个
+    ///
+    /// 共享文件无法读取时跳过该文件，返回所有的错误
+    pub fn merge_into(&self, data: &mut LinkDirSet) -> Vec<Error> {
+        let layers = self
+            .project
+            .iter()
+            .map(|path| (path, Origin::Project))
+            .chain(self.team.iter().map(|path| (path, Origin::Team)));
+
+        let mut errors = Vec::new();
+        for (path, origin) in layers {
+            // 共享文件是只读的，不存在时不会创建
+            match store::read(path) {
+                Ok(layer) => data.merge_layer(layer, origin),
+                Err(err) => errors.push(err),
+            }
+        }
+        errors
+    }
+}
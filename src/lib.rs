@@ -1,89 +1,146 @@
-use std::{
-    fs::{self, File},
-    io::{self, BufWriter, Write},
-    path::{Path, PathBuf},
-};
+#[cfg(feature = "tui")]
+use std::path::Path;
 
+#[cfg(feature = "tui")]
 use ratatui::{Terminal, prelude::Backend};
 
+#[cfg(feature = "tui")]
 use crate::{
     app::{
         App,
         data::{Config, DataTransfer, RuntimeError},
     },
-    data::{dirset::LinkDirSet, link::Link},
+    data::dirset::LinkDirSet,
+    error::Context,
+    profile::DataSource,
+    protocol::Action,
 };
 
+#[cfg(feature = "tui")]
 pub mod app;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "cli")]
+pub mod complete;
 pub mod data;
-pub mod debug;
+pub mod error;
+#[cfg(feature = "export")]
+pub mod export;
+#[cfg(feature = "import")]
+pub mod import;
+#[cfg(feature = "cli")]
+pub mod init;
+#[cfg(feature = "serde-json")]
+pub mod layer;
+#[cfg(feature = "cli")]
+pub mod log;
+#[cfg(feature = "profile")]
+pub mod profile;
+#[cfg(feature = "tui")]
+pub mod protocol;
+#[cfg(feature = "serde-json")]
+pub mod store;
+#[cfg(any(test, feature = "tui"))]
+pub mod temp;
+#[cfg(feature = "tui")]
+pub mod term;
+#[cfg(feature = "tui")]
 pub mod ui;
 
-// 临时函数
-pub fn output_result(bytes: &[u8], path: &PathBuf) -> io::Result<()> {
-    fs::write(path, bytes)
-}
+pub use error::{Error, Result};
+#[cfg(feature = "serde-json")]
+pub use store::Store;
 
-pub fn get_data(data_path: &Path) -> io::Result<LinkDirSet> {
-    if !data_path.is_file() {
-        let mut file = BufWriter::new(File::create(data_path)?);
-        let data = LinkDirSet::new();
-        file.write_all(serde_json::to_vec(&data)?.as_slice())?;
-        Ok(data)
-    } else {
-        let vec = fs::read(data_path)?;
-        let data = serde_json::from_slice(&vec);
-        data.map_err(|err| err.into())
-    }
+/// 读取数据文件，不存在时创建空的数据文件
+#[cfg(feature = "tui")]
+pub(crate) fn get_data(data_path: &Path) -> error::Result<LinkDirSet> {
+    store::load(data_path)
 }
 
-pub fn try_save(save: bool, data_path: &Path, data: &LinkDirSet) -> io::Result<()> {
-    if save {
-        let mut file = BufWriter::new(File::create(data_path)?);
-        file.write_all(serde_json::to_vec(data)?.as_slice())?;
+/// 只保存个人数据，共享的数据不会写入，读取之后没有修改过时不会写入
+#[cfg(feature = "tui")]
+pub(crate) fn try_save(save: bool, data_path: &Path, data: &mut LinkDirSet) -> error::Result<()> {
+    if save && data.is_modified() {
+        let hash = store::save(data_path, &data.personal(), data.file_hash())?;
+        data.set_file_hash(Some(hash));
+        data.mark_saved();
     }
     Ok(())
 }
 
+#[cfg(feature = "tui")]
 pub fn run_app<B: Backend>(
-    data_path: &Path,
-    mut terminal: Terminal<B>,
+    source: DataSource,
+    terminal: &mut Terminal<B>,
     mut config: Config,
-) -> io::Result<Option<Link>> {
-    let (data, read_result) = match get_data(data_path) {
+) -> error::Result<Option<Action>> {
+    let (data, read_result) = match get_data(source.path()) {
         Ok(data) => (data, Ok(())),
-        Err(err) => (LinkDirSet::new(), Err(err)),
+        Err(err) => {
+            tracing::error!(path = %source.path().display(), %err, "failed to read data");
+            (LinkDirSet::new(), Err(err))
+        }
     };
 
     let path = config.path.take();
+    let import = config.import.take();
+    let layers = std::mem::take(&mut config.layers);
+    let events = config.events.take();
+    let sandbox = config.sandbox.take();
+    let viewport = config.viewport;
     let data_transfer = DataTransfer {
         config: Some(config),
-        link: None,
+        action: None,
+        source: None,
         data: None,
     };
 
     let mut runtime = RuntimeError::new();
     runtime.read_data = read_result.err();
 
-    let mut transfer = App::new(data).run(&mut terminal, runtime, data_transfer)?;
-    let link_data_set = transfer.data.take().unwrap();
+    let mut app = App::new(data)
+        .with_viewport(viewport)
+        .with_source(source)
+        .with_layers(layers);
+    if let Some(events) = events {
+        app = app.with_event_source(events);
+    }
+    if let Some(dir) = sandbox {
+        app = app.with_sandbox(dir);
+    }
+    if let Some(imported) = import {
+        app = app.with_import(imported);
+    }
+    let mut transfer = app
+        .run(terminal, runtime, data_transfer)
+        .context("failed to run the TUI")?;
+    let mut link_data_set = transfer.data.take().unwrap();
+    let source = transfer.source.take().unwrap();
 
-    // TODO: handle save error
-    let _result = try_save(
+    // 保存失败时仍然输出选择的结果，之后再返回错误
+    let saved = try_save(
         transfer.config.as_ref().unwrap().save,
-        data_path,
-        &link_data_set,
-    );
+        source.path(),
+        &mut link_data_set,
+    )
+    .inspect_err(|err| tracing::error!(%err, "failed to save data"));
 
-    if let Some(link) = transfer.link() {
-        path.map_or_else(
-            || Ok(Some(link.clone())),
-            |path| {
-                output_result(link.path().as_os_str().as_encoded_bytes(), &path)?;
-                Ok(None)
-            },
-        )
-    } else {
-        Ok(None)
+    let action = transfer.action.take();
+    if let Some(path) = &path {
+        let status = match action {
+            Some(_) => protocol::STATUS_OK,
+            None => protocol::STATUS_CANCELLED,
+        };
+        protocol::write(path, action.as_ref(), status)?;
+    }
+    saved?;
+    match (action, path) {
+        (None, _) => Err(Error::Cancelled),
+        // 结果已经写入文件
+        (Some(_), Some(_)) => Ok(None),
+        (Some(action), None) if action.needs_result_file() => {
+            Err(Error::NeedsResultFile(action.as_str()))
+        }
+        (Some(action), None) => Ok(Some(action)),
     }
 }
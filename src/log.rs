@@ -0,0 +1,55 @@
+use std::{
+    io,
+    path::{self, Path, PathBuf},
+};
+
+use tracing::level_filters::LevelFilter;
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
+
+use crate::profile::DataSource;
+
+pub const LOG_ENV: &str = "DIR_LINK_LOG";
+
+/// 最多保留的日志文件数量，超过时删除最旧的
+const MAX_LOG_FILES: usize = 7;
+
+/// 使用配置时日志保存在数据目录下的`logs`中，
+/// 使用`--data`或者`DIR_LINK_DATA`指定数据文件时保存在数据文件旁边的`logs`中
+///
+/// 相对路径的数据文件先转换为绝对路径，日志不会写到之后所在的其他目录中
+pub fn log_dir(source: &DataSource) -> PathBuf {
+    let dir = source
+        .base_dir()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    path::absolute(dir)
+        .unwrap_or_else(|_| dir.to_path_buf())
+        .join("logs")
+}
+
+/// 把日志按天轮换写入`dir`，TUI占用了终端，所以日志只能写入文件
+///
+/// 返回的`WorkerGuard`被丢弃时才会写完剩余的日志，需要一直持有到程序退出
+pub fn init(dir: &Path, level: LevelFilter) -> io::Result<Option<WorkerGuard>> {
+    if level == LevelFilter::OFF {
+        return Ok(None);
+    }
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("dir_link")
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(io::Error::other)?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+    tracing_subscriber::fmt()
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_max_level(level)
+        .try_init()
+        .map_err(io::Error::other)?;
+    Ok(Some(guard))
+}
@@ -1,34 +1,170 @@
-use std::{env, fs};
+use std::{env, io, process::ExitCode};
 
-use color_eyre::eyre;
-use dir_link::app::data::Config;
-use directories::BaseDirs;
+use clap::Parser;
+use dir_link::{
+    Store,
+    app::{
+        data::Config,
+        event::{CrosstermEvents, EventSource},
+        record::{Recorder, Replay},
+    },
+    cli::{Cli, Command},
+    complete,
+    data::dirset::LinkDirSet,
+    error::{self, Context, Error},
+    export, import, init,
+    layer::Layers,
+    log,
+    profile::DataSource,
+    protocol::{self, Action},
+    temp::PrivateDir,
+    term,
+};
 
-fn main() -> color_eyre::Result<()> {
-    color_eyre::install()?;
+/// 加载合并了共享数据的链接，无法读取的共享文件只输出警告
+///
+/// 只用于不修改数据的命令，数据文件不存在时不会创建
+fn load_data(source: &DataSource, layers: &Layers) -> error::Result<LinkDirSet> {
+    let mut store = Store::open_existing(source.path())?;
+    for err in store.merge_layers(layers) {
+        tracing::warn!(%err, "failed to read shared links");
+        eprintln!("warning: failed to read shared links: {err}");
+    }
+    Ok(store.into_data())
+}
+
+/// 取消时不输出任何信息，其余错误输出到stderr，退出码见[`error`]
+fn main() -> ExitCode {
+    complete::complete();
+
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Error::Cancelled) => ExitCode::from(error::EXIT_CANCELLED),
+        Err(err) => {
+            tracing::error!(%err, code = err.exit_code(), "exit");
+            eprintln!("error: {err}");
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+fn run(cli: Cli) -> error::Result<()> {
+    let viewport = cli.viewport();
+
+    let source = DataSource::resolve(cli.data.clone(), cli.profile.as_deref())?;
+    source.create_dir()?;
+    // 日志无法初始化时不影响正常使用
+    let _log_guard = cli.log_level.and_then(|level| {
+        log::init(&log::log_dir(&source), level)
+            .inspect_err(|err| eprintln!("warning: failed to initialize logging: {err}"))
+            .ok()
+            .flatten()
+    });
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), data = %source.path().display(), "start");
+    let layers = cli.layers();
 
-    // TODO: use clap later
-    let args: Vec<_> = env::args_os().collect();
+    let import = match cli.command {
+        Some(Command::Import {
+            source,
+            from,
+            folder,
+        }) => {
+            let entries = import::load(source, from.as_deref())
+                .context(format!("failed to import from {}", source.as_str()))?;
+            let folder = folder.as_deref().unwrap_or(source.as_str());
+            let imported = import::to_link_dir(folder, entries)?;
+            for entry in &imported.skipped {
+                tracing::warn!(path = %entry.path.display(), "skip invalid bookmark");
+            }
+            Some(imported)
+        }
+        Some(Command::Export { format, folder }) => {
+            let data = load_data(&source, &layers)?;
+            let exported = export::export(format, &data, folder.as_deref())
+                .ok_or_else(|| Error::not_found("folder", folder.unwrap_or_default()))?;
+            for skipped in &exported.skipped {
+                tracing::warn!(link = skipped.link, reason = skipped.reason, "skip link");
+                eprintln!("warning: skipped `{}`: {}", skipped.link, skipped.reason);
+            }
+            print!("{}", exported.text);
+            return Ok(());
+        }
+        Some(Command::Init { shell, cmd }) => {
+            print!("{}", init::init(shell, &cmd));
+            return Ok(());
+        }
+        Some(Command::Go { target }) => {
+            let data = load_data(&source, &layers)?;
+            let link = data
+                .find_link(&target)
+                .ok_or_else(|| Error::not_found("link", target.as_str()))?;
+            let resolved = link.resolve().map_err(|source| Error::Unresolved {
+                path: link.path().to_path_buf(),
+                source,
+            })?;
+            match &cli.output {
+                Some(path) => {
+                    protocol::write(path, Some(&Action::Cd(resolved)), protocol::STATUS_OK)?
+                }
+                None => println!("{}", resolved.display()),
+            }
+            return Ok(());
+        }
+        Some(Command::Completions { shell }) => {
+            complete::write_registration(shell, &mut io::stdout())
+                .context("failed to write the completion script")?;
+            return Ok(());
+        }
+        Some(Command::List) => {
+            let data = load_data(&source, &layers)?;
+            data.link_names().for_each(|name| println!("{name}"));
+            return Ok(());
+        }
+        None => None,
+    };
+    // 重放时数据文件、切换的配置以及共享文件都使用临时目录中的副本，不会修改原来的数据
+    let sandbox = match &cli.replay {
+        Some(_) => {
+            Some(PrivateDir::new("replay").context("failed to create a temporary directory")?)
+        }
+        None => None,
+    };
+    let (source, layers) = match &sandbox {
+        Some(dir) => (
+            source.sandbox(dir.path())?,
+            layers
+                .sandbox(dir.path())
+                .context("failed to copy the shared links")?,
+        ),
+        None => (source, layers),
+    };
+    let events: Option<Box<dyn EventSource>> = match (&cli.record, &cli.replay) {
+        (_, Some(replay)) => Some(Box::new(
+            Replay::open(replay).context(format!("failed to open `{}`", replay.display()))?,
+        )),
+        (Some(record), None) => Some(Box::new(
+            Recorder::create(CrosstermEvents, record)
+                .context(format!("failed to create `{}`", record.display()))?,
+        )),
+        (None, None) => None,
+    };
     let config = Config {
-        path: args.get(1).map(|s| s.into()),
+        path: cli.output,
         save: true,
+        viewport,
+        import,
+        layers,
+        events,
+        sandbox: sandbox.as_ref().map(|dir| dir.path().to_path_buf()),
     };
 
-    let base_dir = BaseDirs::new().unwrap();
-    let mut local_data = base_dir.data_local_dir().to_path_buf();
-    local_data.push("dir_link");
-    fs::create_dir_all(&local_data)?;
-    local_data.push("data.json");
-
-    let terminal = ratatui::init();
-    let result = dir_link::run_app(&local_data, terminal, config);
-    ratatui::restore();
-
-    result.map_or_else(
-        |e| Err(eyre::eyre!(e)),
-        |data| {
-            data.inspect(|link| println!("{:?}", link.path().as_os_str()));
-            Ok(())
-        },
-    )
+    let mut terminal = term::init(viewport).context("failed to initialize the terminal")?;
+    let result = dir_link::run_app(source, &mut terminal, config);
+    term::restore(viewport, &mut terminal);
+    drop(sandbox);
+
+    if let Some(action) = result? {
+        println!("{:?}", action.path().as_os_str());
+    }
+    Ok(())
 }
@@ -0,0 +1,158 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use directories::BaseDirs;
+
+use crate::error::{self, Context, Error};
+
+/// 不指定配置时使用的配置名，对应原来的`data.json`
+pub const DEFAULT_PROFILE: &str = "default";
+/// 直接指定数据文件的环境变量，优先于配置
+pub const DATA_ENV: &str = "DIR_LINK_DATA";
+pub const PROFILE_ENV: &str = "DIR_LINK_PROFILE";
+
+/// 数据文件的位置，使用`--data`指定文件时没有配置名
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataSource {
+    profile: Option<String>,
+    path: PathBuf,
+}
+
+impl DataSource {
+    pub fn file(path: PathBuf) -> Self {
+        Self {
+            profile: None,
+            path,
+        }
+    }
+
+    pub fn profile(name: &str) -> error::Result<Self> {
+        if !is_valid_name(name) {
+            return Err(Error::InvalidProfile(name.to_string()));
+        }
+        let data_dir = data_dir().context("failed to locate the data directory")?;
+        Self::profile_in(&data_dir, name)
+    }
+
+    /// 保存在`data_dir`而不是默认数据目录中的配置
+    pub fn profile_in(data_dir: &Path, name: &str) -> error::Result<Self> {
+        if !is_valid_name(name) {
+            return Err(Error::InvalidProfile(name.to_string()));
+        }
+        Ok(Self {
+            profile: Some(name.to_string()),
+            path: profile_path(data_dir, name),
+        })
+    }
+
+    /// 重放时使用数据文件在`dir`中的副本，之后的修改都不会影响原来的文件
+    ///
+    /// 已经复制过时直接使用之前的副本，`dir`需要是只有当前用户能访问的新目录
+    pub fn sandbox(&self, dir: &Path) -> error::Result<Self> {
+        let path = match &self.profile {
+            Some(name) => profile_path(dir, name),
+            None => dir.join("file.json"),
+        };
+        let source = Self {
+            profile: self.profile.clone(),
+            path,
+        };
+        if !source.path.exists() && self.path.is_file() {
+            source.create_dir()?;
+            fs::copy(&self.path, &source.path)
+                .context(format!("failed to copy `{}`", self.path.display()))?;
+        }
+        Ok(source)
+    }
+
+    /// 按照`--data`、`--profile`的顺序决定数据文件，都没有时使用默认配置
+    pub fn resolve(data: Option<PathBuf>, profile: Option<&str>) -> error::Result<Self> {
+        match (data, profile) {
+            (Some(path), _) => Ok(Self::file(path)),
+            (None, Some(name)) => Self::profile(name),
+            (None, None) => Self::profile(DEFAULT_PROFILE),
+        }
+    }
+
+    /// 只使用环境变量决定数据文件，用于没有命令行参数的补全
+    pub fn from_env() -> error::Result<Self> {
+        let data = env::var_os(DATA_ENV).map(PathBuf::from);
+        let profile = env::var(PROFILE_ENV).ok();
+        Self::resolve(data, profile.as_deref())
+    }
+
+    pub fn profile_name(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 配置所在的数据目录，`--data`指定的文件为其所在的目录，见[`profile_path`]
+    pub fn base_dir(&self) -> Option<&Path> {
+        match self.profile.as_deref() {
+            Some(name) if name != DEFAULT_PROFILE => self.path.parent()?.parent(),
+            _ => self.path.parent(),
+        }
+    }
+
+    /// 创建数据文件所在的目录
+    pub fn create_dir(&self) -> error::Result<()> {
+        match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent)
+                .context(format!("failed to create `{}`", parent.display())),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn data_dir() -> io::Result<PathBuf> {
+    let base_dir = BaseDirs::new().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("home directory not found, use --data or {DATA_ENV} to set the data file"),
+        )
+    })?;
+    Ok(base_dir.data_local_dir().join("dir_link"))
+}
+
+/// 默认配置保存在`data.json`，其余配置保存在`profiles/<name>.json`
+pub fn profile_path(data_dir: &Path, name: &str) -> PathBuf {
+    if name == DEFAULT_PROFILE {
+        data_dir.join("data.json")
+    } else {
+        data_dir.join("profiles").join(format!("{name}.json"))
+    }
+}
+
+/// 已经存在的配置，默认配置总是在第一个
+pub fn list_profiles() -> io::Result<Vec<String>> {
+    let mut profiles = Vec::new();
+    if let Ok(entries) = fs::read_dir(data_dir()?.join("profiles")) {
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json")
+                && let Some(name) = path.file_stem().and_then(|name| name.to_str())
+                && is_valid_name(name)
+                && name != DEFAULT_PROFILE
+            {
+                profiles.push(name.to_string());
+            }
+        }
+    }
+    profiles.sort();
+    profiles.insert(0, DEFAULT_PROFILE.to_string());
+    Ok(profiles)
+}
+
+// 配置名会成为文件名，不能包含路径分隔符
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
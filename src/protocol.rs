@@ -0,0 +1,111 @@
+use std::{
+    ffi::OsStr,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::{self, Context};
+
+/// 结果文件的第一个字段，用于识别格式
+pub const MAGIC: &str = "dir_link";
+/// 格式改变时增加，shell脚本遇到不认识的版本时不做任何操作
+pub const VERSION: u32 = 1;
+
+pub const STATUS_OK: i32 = error::EXIT_OK as i32;
+/// 没有选择任何链接就退出
+pub const STATUS_CANCELLED: i32 = error::EXIT_CANCELLED as i32;
+
+/// 退出TUI之后由shell执行的操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Cd(PathBuf),
+    Pushd(PathBuf),
+    /// 输出路径而不切换目录
+    Print(PathBuf),
+    /// 在目录中执行命令，不改变shell的当前目录
+    ExecCommand {
+        dir: PathBuf,
+        command: String,
+    },
+    /// 切换到目录所在位置后用`$EDITOR`打开
+    OpenEditor(PathBuf),
+}
+
+impl Action {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Cd(_) => "cd",
+            Action::Pushd(_) => "pushd",
+            Action::Print(_) => "print",
+            Action::ExecCommand { .. } => "exec-command",
+            Action::OpenEditor(_) => "open-editor",
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            Action::Cd(path)
+            | Action::Pushd(path)
+            | Action::Print(path)
+            | Action::OpenEditor(path) => path,
+            Action::ExecCommand { dir, .. } => dir,
+        }
+    }
+
+    /// 只有路径的操作可以不通过结果文件，直接把路径输出到stdout
+    pub fn needs_result_file(&self) -> bool {
+        matches!(self, Action::ExecCommand { .. } | Action::OpenEditor(_))
+    }
+
+    fn payload(&self) -> Vec<&OsStr> {
+        match self {
+            Action::ExecCommand { dir, command } => vec![dir.as_os_str(), OsStr::new(command)],
+            _ => vec![self.path().as_os_str()],
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// 编码写入结果文件的内容
+///
+/// 每个字段都以NUL结尾，依次为：`dir_link`、版本号、操作、退出状态、操作的参数。
+/// 没有操作时操作字段为`none`且没有参数。
+///
+/// | 操作           | 参数           |
+/// | -------------- | -------------- |
+/// | `cd`           | 路径           |
+/// | `pushd`        | 路径           |
+/// | `print`        | 路径           |
+/// | `exec-command` | 目录、命令     |
+/// | `open-editor`  | 路径           |
+pub fn encode(action: Option<&Action>, status: i32) -> Vec<u8> {
+    let version = VERSION.to_string();
+    let status = status.to_string();
+    let mut fields = vec![
+        OsStr::new(MAGIC),
+        OsStr::new(&version),
+        OsStr::new(action.map_or("none", Action::as_str)),
+        OsStr::new(&status),
+    ];
+    if let Some(action) = action {
+        fields.extend(action.payload());
+    }
+
+    let mut bytes = Vec::new();
+    for field in fields {
+        bytes.extend_from_slice(field.as_encoded_bytes());
+        bytes.push(0);
+    }
+    bytes
+}
+
+/// 把编码后的结果写入集成脚本传入的文件
+pub fn write(path: &Path, action: Option<&Action>, status: i32) -> error::Result<()> {
+    fs::write(path, encode(action, status)).context(format!("failed to write `{}`", path.display()))
+}
@@ -0,0 +1,337 @@
+use std::{
+    cell::OnceCell,
+    fs::{self, File, TryLockError},
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    data::{dir::LinkDir, dirset::LinkDirSet, index::PathIndex, link::Link, origin::Origin},
+    error::{Context, Error, Result},
+    layer::Layers,
+};
+
+/// 嵌入其他工具时使用的数据文件接口
+///
+/// 读取和保存时会给数据文件加锁。锁只在读写时持有，读取之后数据文件被TUI或者其他进程
+/// 修改过时保存会返回[`Error::Conflict`]而不会覆盖，需要[`Store::reload`]之后重新修改。
+/// 其他进程一直持有锁时返回[`Error::Locked`]。
+/// 合并的共享数据是只读的，保存时只写入个人数据
+#[derive(Debug)]
+pub struct Store {
+    path: PathBuf,
+    data: LinkDirSet,
+    // 第一次按照路径查询时建立，修改数据后清空
+    paths: OnceCell<PathIndex>,
+}
+
+impl Store {
+    /// 打开数据文件，不存在时创建空的数据文件
+    pub fn open<P: Into<PathBuf>>(path: P) -> Result<Self> {
+        let path = path.into();
+        let data = load(&path)?;
+        Ok(Self {
+            path,
+            data,
+            paths: OnceCell::new(),
+        })
+    }
+
+    /// 只读的命令使用，数据文件不存在时返回空的数据而不创建文件
+    pub fn open_existing<P: Into<PathBuf>>(path: P) -> Result<Self> {
+        let path = path.into();
+        let data = if path.is_file() {
+            load(&path)?
+        } else {
+            // 之后保存时其他进程已经创建了数据文件也算冲突
+            let mut data = LinkDirSet::new();
+            data.set_file_hash(Some(content_hash(&[])));
+            data
+        };
+        Ok(Self {
+            path,
+            data,
+            paths: OnceCell::new(),
+        })
+    }
+
+    /// 打开配置对应的数据文件，与`--profile`相同
+    #[cfg(feature = "profile")]
+    pub fn open_profile(name: &str) -> Result<Self> {
+        let source = crate::profile::DataSource::profile(name)?;
+        source.create_dir()?;
+        Self::open(source.path())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn data(&self) -> &LinkDirSet {
+        &self.data
+    }
+
+    pub fn into_data(self) -> LinkDirSet {
+        self.data
+    }
+
+    /// 重新读取数据文件，之前合并的共享数据需要重新合并
+    pub fn reload(&mut self) -> Result<()> {
+        self.data = load(&self.path)?;
+        self.paths.take();
+        Ok(())
+    }
+
+    /// 读取或者上一次保存之后没有修改时不会写入
+    pub fn save(&mut self) -> Result<()> {
+        if self.data.is_modified() {
+            let hash = save(&self.path, &self.data.personal(), self.data.file_hash())?;
+            self.data.set_file_hash(Some(hash));
+            self.data.mark_saved();
+        }
+        Ok(())
+    }
+
+    /// 合并只读的共享数据，返回无法读取的共享文件的错误
+    pub fn merge_layers(&mut self, layers: &Layers) -> Vec<Error> {
+        self.paths.take();
+        layers.merge_into(&mut self.data)
+    }
+
+    pub fn folders(&self) -> impl Iterator<Item = &LinkDir> {
+        self.data.iter()
+    }
+
+    pub fn links(&self) -> impl Iterator<Item = (&LinkDir, &Link)> {
+        self.data
+            .iter()
+            .flat_map(|dir| dir.iter().map(move |link| (dir, link)))
+    }
+
+    pub fn folder(&self, identifier: &str) -> Option<&LinkDir> {
+        self.data.find(identifier)
+    }
+
+    /// 根据`folder/link`查找链接
+    pub fn link(&self, target: &str) -> Option<&Link> {
+        self.data.find_link(target)
+    }
+
+    /// 指向`path`的所有链接，路径模板按照展开后的路径比较
+    pub fn find_path(&self, path: &Path) -> impl Iterator<Item = (&LinkDir, &Link)> + use<'_> {
+        let paths = self.paths.get_or_init(|| PathIndex::new(&self.data));
+        paths.get(path).iter().map(|&(dir, idx)| {
+            let dir = &self.data[dir];
+            (dir, &dir[idx])
+        })
+    }
+
+    pub fn add_folder(&mut self, identifier: &str) -> Result<()> {
+        self.paths.take();
+        self.data.push(LinkDir::builder(identifier)?)?;
+        Ok(())
+    }
+
+    pub fn rename_folder(&mut self, folder: &str, identifier: &str) -> Result<()> {
+        let idx = self.folder_index(folder)?;
+        writable(folder, self.data[idx].origin())?;
+        self.data.rename(idx, identifier)?;
+        Ok(())
+    }
+
+    /// 删除文件夹和其中的所有链接，包含共享链接的文件夹不能删除
+    pub fn remove_folder(&mut self, folder: &str) -> Result<LinkDir> {
+        let idx = self.folder_index(folder)?;
+        if let Some(origin) = self.data[idx].shared_origin() {
+            writable(folder, origin)?;
+        }
+        self.paths.take();
+        Ok(self.data.remove(idx))
+    }
+
+    /// `path`需要是绝对路径或者路径模板
+    pub fn add_link(&mut self, folder: &str, identifier: &str, path: &Path) -> Result<()> {
+        let idx = self.folder_index(folder)?;
+        writable(folder, self.data[idx].origin())?;
+        self.paths.take();
+        self.data
+            .dir_mut(idx)
+            .push(Link::builder(identifier, path)?)?;
+        Ok(())
+    }
+
+    pub fn rename_link(&mut self, folder: &str, link: &str, identifier: &str) -> Result<()> {
+        let (dir, idx) = self.link_index(folder, link)?;
+        self.data.dir_mut(dir).rename(idx, identifier)?;
+        Ok(())
+    }
+
+    pub fn relink(&mut self, folder: &str, link: &str, path: &Path) -> Result<()> {
+        let (dir, idx) = self.link_index(folder, link)?;
+        self.paths.take();
+        self.data.dir_mut(dir).relink(idx, path)?;
+        Ok(())
+    }
+
+    pub fn remove_link(&mut self, folder: &str, link: &str) -> Result<Link> {
+        let (dir, idx) = self.link_index(folder, link)?;
+        self.paths.take();
+        Ok(self.data.dir_mut(dir).remove(idx))
+    }
+
+    fn folder_index(&self, folder: &str) -> Result<usize> {
+        self.data
+            .index_of(folder)
+            .ok_or_else(|| Error::not_found("folder", folder))
+    }
+
+    // 只返回可以修改的个人链接
+    fn link_index(&self, folder: &str, link: &str) -> Result<(usize, usize)> {
+        let dir = self.folder_index(folder)?;
+        let idx = self.data[dir]
+            .index_of(link)
+            .ok_or_else(|| Error::not_found("link", format!("{folder}/{link}")))?;
+        writable(&format!("{folder}/{link}"), self.data[dir][idx].origin())?;
+        Ok((dir, idx))
+    }
+}
+
+fn writable(name: &str, origin: Origin) -> Result<()> {
+    if origin.is_personal() {
+        Ok(())
+    } else {
+        Err(Error::ReadOnly {
+            name: name.to_string(),
+            origin,
+        })
+    }
+}
+
+// 保存时会整个替换数据文件，所以锁加在旁边单独的锁文件上
+fn lock_path(path: &Path) -> PathBuf {
+    path.with_added_extension("lock")
+}
+
+/// 其他进程持有锁超过这个时间时放弃读写，而不是一直等待
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+const LOCK_RETRY: Duration = Duration::from_millis(20);
+
+fn lock(path: &Path, exclusive: bool) -> Result<File> {
+    let lock_path = lock_path(path);
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .context(format!("failed to open `{}`", lock_path.display()))?;
+    let deadline = Instant::now() + LOCK_TIMEOUT;
+    loop {
+        let locked = if exclusive {
+            file.try_lock()
+        } else {
+            file.try_lock_shared()
+        };
+        match locked {
+            Ok(()) => return Ok(file),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => thread::sleep(LOCK_RETRY),
+            Err(TryLockError::WouldBlock) => {
+                return Err(Error::Locked {
+                    path: path.to_path_buf(),
+                });
+            }
+            Err(TryLockError::Error(err)) => {
+                return Err(Error::io(
+                    format!("failed to lock `{}`", path.display()),
+                    err,
+                ));
+            }
+        }
+    }
+}
+
+/// 读取数据文件，不存在时创建空的数据文件
+pub(crate) fn load(path: &Path) -> Result<LinkDirSet> {
+    if !path.is_file() {
+        #[cfg(feature = "tracing")]
+        tracing::info!(path = %path.display(), "create data file");
+        let mut data = LinkDirSet::new();
+        let hash = save(path, &data, Some(content_hash(&[])))?;
+        data.set_file_hash(Some(hash));
+        return Ok(data);
+    }
+    let data = {
+        let _lock = lock(path, false)?;
+        read(path)?
+    };
+    #[cfg(feature = "tracing")]
+    tracing::debug!(path = %path.display(), folders = data.len(), "load data");
+    Ok(data)
+}
+
+/// 读取数据文件，区分无法读取和无法解析两种错误
+///
+/// 不会加锁，共享文件所在的目录可能是只读的
+pub(crate) fn read(path: &Path) -> Result<LinkDirSet> {
+    let vec = fs::read(path).context(format!("failed to read `{}`", path.display()))?;
+    let mut data: LinkDirSet =
+        serde_json::from_slice(&vec).map_err(|source| Error::CorruptData {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    data.set_file_hash(Some(content_hash(&vec)));
+    Ok(data)
+}
+
+/// 数据文件内容的哈希，不存在的文件与空文件相同
+fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 先写入临时文件再替换，写入失败时不会损坏原来的数据，返回写入的内容的哈希
+///
+/// `expected`为读取时的哈希，持有锁之后先检查数据文件是否被其他进程修改过
+pub(crate) fn save(path: &Path, data: &LinkDirSet, expected: Option<u64>) -> Result<u64> {
+    let _lock = lock(path, true)?;
+    if let Some(expected) = expected {
+        let current = match fs::read(path) {
+            Ok(bytes) => content_hash(&bytes),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => content_hash(&[]),
+            Err(err) => {
+                return Err(Error::io(
+                    format!("failed to read `{}`", path.display()),
+                    err,
+                ));
+            }
+        };
+        if current != expected {
+            return Err(Error::Conflict {
+                path: path.to_path_buf(),
+            });
+        }
+    }
+    let bytes = serde_json::to_vec(data)
+        .map_err(io::Error::from)
+        .context(format!("failed to write `{}`", path.display()))?;
+    // 数据文件是符号链接时替换链接指向的文件，而不是链接本身
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let tmp = target.with_added_extension("tmp");
+    let write = || -> io::Result<()> {
+        let mut file = File::create(&tmp)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &target)
+    };
+    write()
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })
+        .context(format!("failed to write `{}`", path.display()))?;
+    #[cfg(feature = "tracing")]
+    tracing::debug!(path = %path.display(), "save data");
+    Ok(content_hash(&bytes))
+}
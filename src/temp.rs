@@ -0,0 +1,91 @@
+use std::{
+    env,
+    fs::{self, DirBuilder, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicU32, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// 只有当前用户能访问的临时目录，离开作用域时连同其中的文件一起删除
+///
+/// 共享的`/tmp`中其他用户可以预先创建同名的文件或者符号链接，
+/// 因此目录必须由自己新建，已经存在时换一个名字重试
+#[derive(Debug)]
+pub struct PrivateDir {
+    path: PathBuf,
+}
+
+impl PrivateDir {
+    const ATTEMPTS: u32 = 16;
+
+    pub fn new(prefix: &str) -> io::Result<Self> {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.subsec_nanos());
+        for _ in 0..Self::ATTEMPTS {
+            let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+            let path = env::temp_dir().join(format!(
+                "dir_link-{prefix}-{}-{nanos:x}-{count}",
+                process::id()
+            ));
+            match private_dir_builder().create(&path) {
+                Ok(()) => return Ok(Self { path }),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "failed to create a unique temporary directory",
+        ))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 新建只有当前用户能读写的文件，文件已经存在时返回错误而不是覆盖
+    pub fn create_file(&self, name: &str) -> io::Result<(File, PathBuf)> {
+        let path = self.path.join(name);
+        let file = private_open_options()
+            .create_new(true)
+            .write(true)
+            .open(&path)?;
+        Ok((file, path))
+    }
+}
+
+impl Drop for PrivateDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(unix)]
+fn private_dir_builder() -> DirBuilder {
+    use std::os::unix::fs::DirBuilderExt;
+    let mut builder = DirBuilder::new();
+    builder.mode(0o700);
+    builder
+}
+
+#[cfg(not(unix))]
+fn private_dir_builder() -> DirBuilder {
+    DirBuilder::new()
+}
+
+#[cfg(unix)]
+fn private_open_options() -> OpenOptions {
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut options = OpenOptions::new();
+    options.mode(0o600);
+    options
+}
+
+#[cfg(not(unix))]
+fn private_open_options() -> OpenOptions {
+    OpenOptions::new()
+}
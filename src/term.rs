@@ -0,0 +1,216 @@
+use std::io::{self, stdout};
+
+use ratatui::{
+    DefaultTerminal, Terminal, TerminalOptions, Viewport,
+    crossterm::{
+        event::{DisableBracketedPaste, EnableBracketedPaste},
+        execute,
+        terminal::{
+            self, EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+        },
+    },
+    layout::{Position, Rect},
+    prelude::Backend,
+};
+
+/// fzf的`--min-height`默认值是10，这里需要能够放下链接编辑框
+pub const MIN_INLINE_HEIGHT: u16 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Height {
+    Lines(u16),
+    Percent(u16),
+}
+
+impl Height {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        match input.strip_suffix('%') {
+            Some(percent) => {
+                let percent: u16 = percent
+                    .parse()
+                    .map_err(|_| format!("invalid height percent: {input}"))?;
+                if percent == 0 || percent > 100 {
+                    return Err(format!("height percent out of range: {input}"));
+                }
+                Ok(Height::Percent(percent))
+            }
+            None => {
+                let lines: u16 = input
+                    .parse()
+                    .map_err(|_| format!("invalid height: {input}"))?;
+                if lines == 0 {
+                    return Err(String::from("height must be greater than 0"));
+                }
+                Ok(Height::Lines(lines))
+            }
+        }
+    }
+
+    /// 根据终端的行数计算实际的行数，结果不小于[`MIN_INLINE_HEIGHT`]且不超过终端高度
+    pub fn lines(&self, rows: u16) -> u16 {
+        let lines = match *self {
+            Height::Lines(lines) => lines,
+            Height::Percent(percent) => (rows as u32 * percent as u32 / 100) as u16,
+        };
+        lines.max(MIN_INLINE_HEIGHT).min(rows.max(1))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewportMode {
+    #[default]
+    Fullscreen,
+    Inline(Height),
+}
+
+impl ViewportMode {
+    pub fn viewport(&self) -> io::Result<Viewport> {
+        match self {
+            ViewportMode::Fullscreen => Ok(Viewport::Fullscreen),
+            ViewportMode::Inline(_) => {
+                let (_, rows) = terminal::size()?;
+                Ok(self.viewport_for(rows))
+            }
+        }
+    }
+
+    /// 终端有`rows`行时的视口
+    fn viewport_for(&self, rows: u16) -> Viewport {
+        match self {
+            ViewportMode::Fullscreen => Viewport::Fullscreen,
+            ViewportMode::Inline(height) => Viewport::Inline(height.lines(rows)),
+        }
+    }
+}
+
+pub fn init(mode: ViewportMode) -> io::Result<DefaultTerminal> {
+    let terminal = match mode {
+        ViewportMode::Fullscreen => ratatui::try_init()?,
+        ViewportMode::Inline(_) => {
+            let viewport = mode.viewport()?;
+            ratatui::try_init_with_options(TerminalOptions { viewport })?
+        }
+    };
+    // 粘贴的内容作为一个整体的`Event::Paste`，而不是一连串的按键
+    execute!(stdout(), EnableBracketedPaste)?;
+    Ok(terminal)
+}
+
+pub fn restore(mode: ViewportMode, terminal: &mut DefaultTerminal) {
+    if let Err(err) = execute!(stdout(), DisableBracketedPaste) {
+        eprintln!("Failed to restore terminal: {err}");
+    }
+    match mode {
+        ViewportMode::Fullscreen => ratatui::restore(),
+        ViewportMode::Inline(_) => {
+            // 清除内联视口，使光标回到视口的起始位置，不在回滚区中留下界面
+            let result = terminal.clear().and_then(|_| disable_raw_mode());
+            if let Err(err) = result {
+                eprintln!("Failed to restore terminal: {err}");
+            }
+        }
+    }
+}
+
+/// 暂时把终端交还给shell或者其他程序，之后需要调用[`resume`]
+pub fn suspend<B: Backend>(mode: ViewportMode, terminal: &mut Terminal<B>) -> io::Result<()> {
+    execute!(stdout(), DisableBracketedPaste)?;
+    match mode {
+        ViewportMode::Fullscreen => {
+            disable_raw_mode()?;
+            execute!(stdout(), LeaveAlternateScreen)?;
+        }
+        ViewportMode::Inline(_) => {
+            terminal.clear()?;
+            disable_raw_mode()?;
+        }
+    }
+    terminal.show_cursor()
+}
+
+pub fn resume<B: Backend>(mode: ViewportMode, terminal: &mut Terminal<B>) -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(stdout(), EnableBracketedPaste)?;
+    match mode {
+        ViewportMode::Fullscreen => {
+            execute!(stdout(), EnterAlternateScreen)?;
+            terminal.clear()
+        }
+        // 其他程序的输出可能让光标移动了，需要重新计算内联视口的位置
+        ViewportMode::Inline(_) => {
+            terminal.resize(Rect::from((Position::ORIGIN, terminal.size()?)))
+        }
+    }
+}
+
+/// 像其他TUI程序一样响应Ctrl+Z，进程在收到SIGCONT之后才会从该函数返回
+pub fn raise_suspend() {
+    #[cfg(unix)]
+    unsafe {
+        libc::raise(libc::SIGTSTP);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lines_and_percent() {
+        assert_eq!(Height::parse("40%"), Ok(Height::Percent(40)));
+        assert_eq!(Height::parse(" 100% "), Ok(Height::Percent(100)));
+        assert_eq!(Height::parse("10"), Ok(Height::Lines(10)));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_height() {
+        assert_eq!(
+            Height::parse("0"),
+            Err("height must be greater than 0".to_string())
+        );
+        assert_eq!(
+            Height::parse("0%"),
+            Err("height percent out of range: 0%".to_string())
+        );
+        assert_eq!(
+            Height::parse("101%"),
+            Err("height percent out of range: 101%".to_string())
+        );
+        assert_eq!(Height::parse("abc"), Err("invalid height: abc".to_string()));
+        assert_eq!(
+            Height::parse("x%"),
+            Err("invalid height percent: x%".to_string())
+        );
+        assert!(Height::parse("").is_err());
+        assert!(Height::parse("-5").is_err());
+    }
+
+    #[test]
+    fn lines_are_clamped_to_terminal() {
+        // 百分比向下取整，但是不小于最小高度
+        assert_eq!(Height::Percent(40).lines(50), 20);
+        assert_eq!(Height::Percent(40).lines(20), MIN_INLINE_HEIGHT);
+        assert_eq!(Height::Lines(10).lines(50), MIN_INLINE_HEIGHT);
+        assert_eq!(Height::Lines(30).lines(50), 30);
+        // 终端比最小高度还矮时使用整个终端
+        assert_eq!(Height::Lines(30).lines(8), 8);
+        assert_eq!(Height::Percent(100).lines(0), 1);
+    }
+
+    #[test]
+    fn inline_viewport_uses_terminal_rows() {
+        assert_eq!(
+            ViewportMode::Inline(Height::Percent(50)).viewport_for(40),
+            Viewport::Inline(20)
+        );
+        assert_eq!(
+            ViewportMode::Inline(Height::Lines(15)).viewport_for(10),
+            Viewport::Inline(10)
+        );
+        assert_eq!(
+            ViewportMode::Fullscreen.viewport_for(40),
+            Viewport::Fullscreen
+        );
+    }
+}
@@ -1,6 +1,8 @@
 pub mod common;
 
-use std::ops::Add;
+use std::ops::{Add, Range};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use ratatui::prelude::*;
 use ratatui::style::Styled;
@@ -13,26 +15,100 @@ use tui_input::Input;
 use unicode_width::UnicodeWidthStr;
 
 use crate::app::App;
-use crate::app::data::CursorCache;
+use crate::app::data::{CursorCache, Toast};
+use crate::app::float::command::CommandState;
 use crate::app::float::confirm::{
-    ConfirmChoice, FolderDeleteConfirmState, FolderSaveConfirmState, LinkDeleteConfirmState,
-    LinkSaveConfirmState,
+    ConfirmChoice, ConfirmState, FolderSaveConfirmState, LinkSaveConfirmState,
 };
 use crate::app::float::edit::{FolderEditState, LinkEditState};
 use crate::app::float::help::HelpState;
-use crate::app::float::warning::{CorruptDataWarningChoice, CorruptDataWarningState, WarningState};
-use crate::app::normal::{FolderNormalState, InputMode, InputPart, LinkNormalState};
-use crate::data::dir::LinkDir;
-use crate::data::dirset::LinkDirSet;
-
-pub fn render_main_border(area: Rect, buf: &mut Buffer) {
+use crate::app::float::import::{ImportPreviewChoice, ImportPreviewState};
+use crate::app::float::profile::ProfileState;
+use crate::app::float::warning::{
+    BulkEditErrorChoice, BulkEditErrorState, CorruptDataWarningChoice, CorruptDataWarningState,
+    WarningState,
+};
+use crate::app::normal::{InputMode, InputPart};
+use crate::app::worker::{Query, QueryCache, QueryKind, QueryResult, QueryState};
+use crate::data::escape;
+use crate::data::link::Link;
+use crate::data::origin::Origin;
+use crate::data::template::ExpandError;
+
+pub fn render_main_border(profile: Option<&str>, area: Rect, buf: &mut Buffer) {
+    let mut title = Line::styled("Dir Link", Style::default().fg(Color::Yellow));
+    if let Some(profile) = profile {
+        title.push_span(Span::styled(
+            format!(" [{profile}]"),
+            Style::default().fg(Color::LightBlue),
+        ));
+    }
     let block = Block::bordered()
-        .title_top(Line::styled("Dir Link", Style::default().fg(Color::Yellow)).left_aligned())
+        .title_top(title.left_aligned())
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(Color::White));
     block.render(area, buf);
 }
 
+pub fn render_toast(toast: &Toast, area: Rect, buf: &mut Buffer) {
+    if area.height == 0 || area.width < 4 {
+        return;
+    }
+    // 显示在主边框底部的右侧
+    let area = Rect {
+        x: area.x + 2,
+        y: area.bottom() - 1,
+        width: area.width - 4,
+        height: 1,
+    };
+    Line::from(format!(" {} ", toast.message()))
+        .style(Style::default().fg(Color::Black).bg(Color::LightGreen))
+        .right_aligned()
+        .render(area, buf);
+}
+
+/// 选中链接的目录信息，显示在主边框底部的左侧，会被提示消息覆盖
+pub fn render_link_details(path: &Path, queries: &QueryCache, area: Rect, buf: &mut Buffer) {
+    if area.height == 0 || area.width < 4 {
+        return;
+    }
+    let now = Instant::now();
+    let mut details = [QueryKind::Preview, QueryKind::GitStatus, QueryKind::Size]
+        .into_iter()
+        .filter_map(
+            |kind| match queries.get(&Query::new(path.to_path_buf(), kind), now) {
+                QueryState::Checking => Some("checking…".to_string()),
+                QueryState::TimedOut => Some("timed out".to_string()),
+                QueryState::Done(QueryResult::Preview(preview)) => {
+                    preview.as_ref().map(ToString::to_string)
+                }
+                QueryState::Done(QueryResult::GitStatus(status)) => {
+                    status.as_ref().map(ToString::to_string)
+                }
+                QueryState::Done(QueryResult::Size(size)) => Some(size.to_string()),
+                QueryState::Done(QueryResult::Exists(_)) => None,
+            },
+        )
+        .collect::<Vec<_>>();
+    // 几项查询都在进行时只显示一次`checking…`
+    details.dedup();
+    if details.is_empty() {
+        return;
+    }
+    let area = Rect {
+        x: area.x + 2,
+        y: area.bottom() - 1,
+        width: area.width - 4,
+        height: 1,
+    };
+    Line::styled(
+        format!(" {} ", details.join(" · ")),
+        Style::default().fg(Color::DarkGray),
+    )
+    .left_aligned()
+    .render(area, buf);
+}
+
 pub fn render_main_divider(area: Rect, buf: &mut Buffer) {
     let block = Block::default()
         .borders(Borders::RIGHT | Borders::LEFT)
@@ -46,12 +122,11 @@ pub fn render_left_list<'a>(
     buf: &mut Buffer,
     default: &'a mut ListState,
 ) {
-    let list = List::new(
-        app.data
-            .map()
-            .iter()
-            .map(|dir| ListItem::new(dir.identifier().set_style(Color::Cyan))),
-    )
+    let list = List::new(app.data.map().iter().map(|dir| {
+        let mut line = Line::from(dir.identifier().set_style(Color::Cyan));
+        line.extend(origin_tag(dir.origin()));
+        ListItem::new(line)
+    }))
     .highlight_style(Style::default().bg(Color::Cyan).fg(Color::Black))
     .highlight_spacing(HighlightSpacing::Always);
 
@@ -91,11 +166,34 @@ pub fn render_right_list<'a>(
                 .height(1)
                 .style(header_style);
 
-            let rows = app.data[idx].iter().map(|link| {
+            let select = app.state.link_table_state_mut().unwrap_or(default);
+            // 只生成可见的行，减去表头的一行
+            let links = &app.data[idx];
+            let visible = visible_rows(select, links.len(), area.height.saturating_sub(1).into());
+            let mut window = TableState::default().with_selected(
+                select
+                    .selected()
+                    .map(|idx| idx.saturating_sub(visible.start)),
+            );
+
+            let queries = &app.cache.queries;
+            let repo = app.cache.repo_root.as_deref();
+            let now = Instant::now();
+            let rows = links[visible.clone()].iter().map(|link| {
                 let identifier = link.identifier();
-                let path = link.path().to_string_lossy();
-                let item = [identifier.to_string(), path.to_string()];
-                item.into_iter().map(Cell::from).collect::<Row>().height(1)
+                // 每行只展开一次
+                let resolved = link.resolve_in(repo);
+                let inactive = link.is_repo_relative() && resolved.is_err();
+                let path = link_path_cell(link, resolved, queries, now);
+                let mut name = Line::from(identifier.to_string());
+                name.extend(origin_tag(link.origin()));
+                let row = Row::new([Cell::from(name), Cell::from(path)]);
+                // 不在git仓库中时仓库内的链接不可用
+                if inactive {
+                    row.style(Style::default().fg(Color::DarkGray)).height(1)
+                } else {
+                    row.height(1)
+                }
             });
             let table = Table::new(
                 rows,
@@ -105,11 +203,13 @@ pub fn render_right_list<'a>(
             .row_highlight_style(Style::default().bg(Color::Cyan).fg(Color::Black))
             .highlight_spacing(HighlightSpacing::Always);
 
-            let select = app.state.link_table_state_mut().unwrap_or(default);
-
-            <Table as StatefulWidget>::render(table, area, buf, select);
+            <Table as StatefulWidget>::render(table, area, buf, &mut window);
+            *select.offset_mut() = visible.start;
+            // 事件循环只为可见的链接提交查询
+            app.cache.visible_links = visible;
         }
         _ => {
+            app.cache.visible_links = 0..0;
             let area = common::center(area, Constraint::Length(5), Constraint::Length(1));
             let focused = app.state.is_link();
             render_right_list_empty(area, buf, focused);
@@ -117,6 +217,72 @@ pub fn render_right_list<'a>(
     };
 }
 
+/// 与`Table`相同的滚动方式：保持之前的偏移，只在选中的行不可见时滚动到刚好可见
+fn visible_rows(state: &TableState, len: usize, height: usize) -> Range<usize> {
+    let mut offset = state.offset().min(len.saturating_sub(1));
+    if let Some(selected) = state.selected().map(|idx| idx.min(len.saturating_sub(1))) {
+        if selected < offset {
+            offset = selected;
+        } else if selected >= offset + height {
+            offset = (selected + 1).saturating_sub(height);
+        }
+    }
+    offset..(offset + height).min(len)
+}
+
+/// 模板同时显示模板和展开后的路径，无法展开时显示原因
+///
+/// 路径是否存在由后台线程检查，没有结果之前显示`checking…`
+fn link_path_cell(
+    link: &Link,
+    resolved: Result<PathBuf, ExpandError>,
+    queries: &QueryCache,
+    now: Instant,
+) -> Line<'static> {
+    let template = escape::escape(link.path().as_os_str());
+    let resolved = match resolved {
+        Ok(resolved) => resolved,
+        Err(ExpandError::NotInRepo) if link.is_repo_relative() => {
+            return Line::from(format!("{template} (inactive)"));
+        }
+        Err(err) => {
+            return Line::styled(
+                format!("{template} ({err})"),
+                Style::default().fg(Color::Red),
+            );
+        }
+    };
+    let query = Query::new(resolved.clone(), QueryKind::Exists);
+    let (style, status) = match queries.get(&query, now) {
+        QueryState::Done(QueryResult::Exists(false)) => (Style::default().fg(Color::Red), None),
+        QueryState::Done(_) => (Style::default(), None),
+        QueryState::Checking => (Style::default(), Some(" (checking…)")),
+        QueryState::TimedOut => (Style::default(), Some(" (timed out)")),
+    };
+    let path = if link.is_template() {
+        let resolved = escape::escape(resolved.as_os_str());
+        format!("{template} → {resolved}")
+    } else {
+        template
+    };
+    let mut line = Line::styled(path, style);
+    if let Some(status) = status {
+        line.push_span(Span::styled(status, Style::default().fg(Color::DarkGray)));
+    }
+    line
+}
+
+/// 共享数据在名称后面标注来源，个人数据不标注
+pub fn origin_tag(origin: Origin) -> Option<Span<'static>> {
+    match origin {
+        Origin::Personal => None,
+        origin => Some(Span::styled(
+            format!(" [{}]", origin.as_str()),
+            Style::default().fg(Color::DarkGray),
+        )),
+    }
+}
+
 pub fn render_right_list_empty(area: Rect, buf: &mut Buffer, focused: bool) {
     let style = if focused {
         Style::default()
@@ -277,6 +443,39 @@ pub fn render_folder_edit(
     );
 }
 
+pub fn render_command(
+    state: &mut CommandState,
+    area: Rect,
+    buf: &mut Buffer,
+    cursor_cache: &mut CursorCache,
+) {
+    let block = Block::bordered()
+        .border_style(Style::default().fg(Color::White))
+        .title_top(Line::from("Run Command").centered())
+        .title_bottom(
+            Line::from(state.dir().to_string_lossy().into_owned())
+                .set_style(Color::Cyan)
+                .left_aligned(),
+        )
+        .border_type(BorderType::Thick);
+    block.render(area, buf);
+
+    let chunk = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0)])
+        .margin(1)
+        .split(area)[0];
+    render_input(
+        state.input_mut(),
+        "Command to run in the link directory:",
+        InputMode::Editing,
+        chunk,
+        buf,
+        None,
+        cursor_cache,
+    );
+}
+
 pub fn render_link_edit(
     state: &mut LinkEditState,
     area: Rect,
@@ -328,34 +527,7 @@ pub fn render_confirm_yes_no_choice(area: Rect, buf: &mut Buffer, choice: Confir
     common::render_comfirm_choice(area, buf, messages, choice, (1, 2));
 }
 
-pub fn render_folder_delete_confirm_float<F>(
-    state: &FolderDeleteConfirmState<F>,
-    area: Rect,
-    buf: &mut Buffer,
-) where
-    F: FnOnce(ConfirmChoice, &mut FolderNormalState, &mut LinkDirSet),
-{
-    render_confirm_border(area, buf);
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(area);
-
-    let hint_message = "Are you sure to DELETE this folder?";
-    render_confirm_message(chunks[0], buf, hint_message);
-
-    render_confirm_yes_no_choice(chunks[1], buf, state.choice());
-}
-
-pub fn render_link_delete_confirm_float<F>(
-    state: &LinkDeleteConfirmState<F>,
-    area: Rect,
-    buf: &mut Buffer,
-) where
-    F: FnOnce(ConfirmChoice, &mut LinkNormalState, &mut LinkDir),
-{
+pub fn render_confirm_float(state: &ConfirmState, area: Rect, buf: &mut Buffer) {
     render_confirm_border(area, buf);
 
     let chunks = Layout::default()
@@ -364,8 +536,7 @@ pub fn render_link_delete_confirm_float<F>(
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(area);
 
-    let hint_message = "Are you sure to DELETE this link?";
-    render_confirm_message(chunks[0], buf, hint_message);
+    render_confirm_message(chunks[0], buf, state.operation().prompt());
 
     render_confirm_yes_no_choice(chunks[1], buf, state.choice());
 }
@@ -495,6 +666,128 @@ pub fn render_corrupt_data_warning_float(
     common::render_comfirm_choice(chunks[1], buf, messages, choice, (1, 2));
 }
 
+pub fn render_bulk_edit_error_float(state: &BulkEditErrorState, area: Rect, buf: &mut Buffer) {
+    let hint_message = "Choose an option and press <Enter>";
+
+    let chunk = common::render_border(
+        Some(Line::from("Bulk Edit Error").style(Style::default().fg(Color::Red))),
+        Some(Line::from(hint_message).style(Style::default().fg(Color::LightGreen))),
+        Style::default().fg(Color::White),
+        area,
+        buf,
+    );
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunk);
+
+    let hint_message = format!(
+        "Failed to apply changes to `{}`\n{}",
+        state.folder(),
+        state.message()
+    );
+    render_confirm_message(chunks[0], buf, &hint_message);
+
+    let messages = ["Edit Again", "Discard"];
+    let choice = match state.choice() {
+        BulkEditErrorChoice::Edit => 0,
+        BulkEditErrorChoice::Discard => 1,
+    };
+    common::render_comfirm_choice(chunks[1], buf, messages, choice, (1, 2));
+}
+
+pub fn render_import_preview_float(state: &mut ImportPreviewState, area: Rect, buf: &mut Buffer) {
+    let hint_message = "<Up>/<Down> to Scroll, <Enter> to Choose";
+    let title = match state.skipped() {
+        0 => format!("Import {} Links", state.items().len()),
+        skipped => format!("Import {} Links, {skipped} skipped", state.items().len()),
+    };
+
+    let chunk = common::render_border(
+        Some(Line::from(title).style(Style::default().fg(Color::Yellow))),
+        Some(Line::from(hint_message).style(Style::default().fg(Color::LightGreen))),
+        Style::default().fg(Color::White),
+        area,
+        buf,
+    );
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(3)])
+        .split(chunk);
+
+    // 新建的文件夹用`+`标出
+    let rows: Vec<_> = state
+        .items()
+        .iter()
+        .map(|item| {
+            let folder = if item.new_folder {
+                format!("+{}", item.folder)
+            } else {
+                item.folder.clone()
+            };
+            Row::new([
+                Cell::from(Text::from(folder).set_style(Color::LightBlue)),
+                Cell::from(
+                    Text::from(item.link.identifier().to_string()).set_style(Color::LightGreen),
+                ),
+                Cell::from(
+                    Text::from(item.link.path().to_string_lossy().into_owned())
+                        .set_style(Color::White),
+                ),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(20),
+            Constraint::Percentage(25),
+            Constraint::Percentage(55),
+        ],
+    )
+    .row_highlight_style(Style::default().bg(Color::Cyan).fg(Color::Black));
+    <Table as StatefulWidget>::render(table, chunks[0], buf, state.table_state_mut());
+
+    let messages = ["Import", "Cancel"];
+    let choice = match state.choice() {
+        ImportPreviewChoice::Import => 0,
+        ImportPreviewChoice::Cancel => 1,
+    };
+    common::render_comfirm_choice(chunks[1], buf, messages, choice, (1, 2));
+}
+
+pub fn render_profile_float(state: &mut ProfileState, area: Rect, buf: &mut Buffer) {
+    let hint_message = "<Enter> to Switch";
+
+    let chunk = common::render_border(
+        Some(Line::from("Profiles").style(Style::default().fg(Color::Yellow))),
+        Some(Line::from(hint_message).style(Style::default().fg(Color::LightGreen))),
+        Style::default().fg(Color::White),
+        area,
+        buf,
+    );
+
+    // 当前使用的配置用`*`标出
+    let items: Vec<_> = state
+        .profiles()
+        .iter()
+        .map(|name| {
+            let mark = if state.current() == Some(name.as_str()) {
+                "* "
+            } else {
+                "  "
+            };
+            ListItem::new(format!("{mark}{name}").set_style(Color::Cyan))
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(Style::default().bg(Color::Cyan).fg(Color::Black))
+        .highlight_spacing(HighlightSpacing::Always);
+    <List as StatefulWidget>::render(list, chunk, buf, state.list_state_mut());
+}
+
 pub fn render_help_float(state: &HelpState, area: Rect, buf: &mut Buffer) {
     let hint_message = "Press <Esc>/<Q> to Quit Help";
 
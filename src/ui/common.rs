@@ -36,6 +36,21 @@ pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1] // Return the middle chunk
 }
 
+/// same as [`centered_rect`], but the height is at least `min_height` if the available rect allows,
+/// so that floats are still usable in a small inline viewport
+pub fn centered_rect_min(percent_x: u16, percent_y: u16, min_height: u16, r: Rect) -> Rect {
+    let rect = centered_rect(percent_x, percent_y, r);
+    if rect.height >= min_height {
+        return rect;
+    }
+    let height = min_height.min(r.height);
+    Rect {
+        y: r.y + (r.height - height) / 2,
+        height,
+        ..rect
+    }
+}
+
 pub fn centered_text(text: &str, area: Rect, additional_x: u16, additional_y: u16) -> Rect {
     let lines: Vec<_> = text.lines().collect();
     let lines_width: Vec<_> = lines
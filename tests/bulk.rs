@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use dir_link::data::{
+    bulk::{self, Summary},
+    dir::LinkDir,
+    dirset::LinkDirSet,
+    link::Link,
+};
+
+fn links(pairs: &[(&str, &str)]) -> Vec<Link> {
+    pairs
+        .iter()
+        .map(|(identifier, path)| Link::builder(identifier, Path::new(path)).unwrap())
+        .collect()
+}
+
+#[test]
+fn dump_and_parse_round_trip() {
+    let mut dir = LinkDir::builder("work").unwrap();
+    for link in links(&[("api", "/srv/api"), ("docs", "/srv/docs\tarchive")]) {
+        dir.push(link).unwrap();
+    }
+    let text = bulk::dump(&dir);
+    assert_eq!(
+        bulk::parse(&text).unwrap(),
+        dir.iter().cloned().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn parse_skips_blank_lines() {
+    assert_eq!(
+        bulk::parse("\napi\t/srv/api\n   \ndocs\t/srv/docs\n").unwrap(),
+        links(&[("api", "/srv/api"), ("docs", "/srv/docs")])
+    );
+}
+
+#[test]
+fn parse_reports_line_numbers() {
+    let err = bulk::parse("api\t/srv/api\n\nno tab here\n").unwrap_err();
+    assert_eq!(err.line(), 3);
+
+    let err = bulk::parse("api\t/srv/api\napi\t/srv/other\n").unwrap_err();
+    assert_eq!(err.line(), 2);
+    assert_eq!(err.message(), "duplicated link identifier `api`");
+
+    let err = bulk::parse("api\trelative/path\n").unwrap_err();
+    assert_eq!(err.line(), 1);
+}
+
+#[test]
+fn diff_matches_by_identifier_then_path() {
+    let old = links(&[("api", "/srv/api"), ("docs", "/srv/docs"), ("tmp", "/tmp")]);
+    let new = links(&[
+        ("documents", "/srv/docs"),
+        ("api", "/srv/api2"),
+        ("home", "/home"),
+    ]);
+    assert_eq!(
+        Summary::diff(&old, &new),
+        Summary {
+            renamed: 1,
+            relinked: 1,
+            removed: 1,
+            added: 1,
+            reordered: true,
+        }
+    );
+    assert_eq!(
+        Summary::diff(&old, &new).to_string(),
+        "1 renamed, 1 relinked, 1 removed, 1 added, reordered"
+    );
+    assert_eq!(Summary::diff(&old, &old).to_string(), "No changes");
+}
+
+#[test]
+fn apply_keeps_folder_on_error() {
+    let mut data = LinkDirSet::new();
+    let mut dir = LinkDir::builder("work").unwrap();
+    for link in links(&[("api", "/srv/api"), ("docs", "/srv/docs")]) {
+        dir.push(link).unwrap();
+    }
+    data.push(dir).unwrap();
+    let before = data[0].iter().cloned().collect::<Vec<_>>();
+
+    assert!(bulk::apply(&mut data.dir_mut(0), "api\n").is_err());
+    assert_eq!(data[0].iter().cloned().collect::<Vec<_>>(), before);
+
+    let summary = bulk::apply(&mut data.dir_mut(0), "docs\t/srv/docs\n").unwrap();
+    assert_eq!(summary.removed, 1);
+    assert_eq!(data[0].len(), 1);
+    assert!(data.is_consistent());
+}
@@ -0,0 +1,28 @@
+// 每个测试文件只使用其中的一部分
+#![allow(dead_code)]
+
+use std::path::Path;
+
+use dir_link::data::{dir::LinkDir, dirset::LinkDirSet, link::Link};
+
+/// 由`(文件夹, [(链接, 路径)])`生成数据
+pub fn data(folders: &[(&str, &[(&str, &str)])]) -> LinkDirSet {
+    let mut data = LinkDirSet::new();
+    for (folder, links) in folders {
+        let mut dir = LinkDir::builder(folder).unwrap();
+        for (identifier, path) in *links {
+            dir.push(Link::builder(identifier, Path::new(path)).unwrap())
+                .unwrap();
+        }
+        data.push(dir).unwrap();
+    }
+    data
+}
+
+/// `work`中有`api`和`docs`，`home`中有`conf`
+pub fn sample() -> LinkDirSet {
+    data(&[
+        ("work", &[("api", "/"), ("docs", "/tmp")]),
+        ("home", &[("conf", "/")]),
+    ])
+}
@@ -0,0 +1,115 @@
+use std::path::Path;
+
+use dir_link::{
+    data::{dir::LinkDir, dirset::LinkDirSet, link::Link},
+    export::{self, Format, Skipped},
+};
+
+fn data_with(links: &[(&str, &Path)]) -> LinkDirSet {
+    let mut dir = LinkDir::builder("work").unwrap();
+    for (identifier, path) in links {
+        dir.push(Link::builder(identifier, path).unwrap()).unwrap();
+    }
+    let mut data = LinkDirSet::new();
+    data.push(dir).unwrap();
+    data
+}
+
+fn text(format: Format, data: &LinkDirSet) -> String {
+    export::export(format, data, None).unwrap().text
+}
+
+#[test]
+fn exports_shell_formats() {
+    let data = data_with(&[("api", Path::new("/srv/it's api"))]);
+    assert_eq!(
+        text(Format::Hash, &data),
+        "hash -d work_api='/srv/it'\\''s api'\n"
+    );
+    assert_eq!(
+        text(Format::Bash, &data),
+        "alias work_api='cd '\\''/srv/it'\\''\\'\\'''\\''s api'\\'''\n"
+    );
+    assert_eq!(
+        text(Format::Fish, &data),
+        "alias work_api 'cd \\'/srv/it\\\\\\'s api\\''\n"
+    );
+    assert_eq!(text(Format::Gtk, &data), "file:///srv/it%27s%20api api\n");
+    assert_eq!(text(Format::Cdpath, &data), "/srv/it's api\n");
+    assert!(export::export(Format::Hash, &data, Some("missing")).is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn non_utf8_paths_are_exported_byte_exact() {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    let path = Path::new(OsStr::from_bytes(b"/srv/caf\xe9 x"));
+    let data = data_with(&[("cafe", path), ("api", Path::new("/srv/api"))]);
+    assert_eq!(
+        text(Format::Hash, &data),
+        "hash -d work_cafe=$'/srv/caf\\xe9 x'\nhash -d work_api='/srv/api'\n"
+    );
+    assert_eq!(
+        text(Format::Env, &data),
+        "export DL_work_cafe=$'/srv/caf\\xe9 x'\nexport DL_work_api='/srv/api'\n"
+    );
+    assert_eq!(
+        text(Format::Fish, &data),
+        "alias work_cafe 'cd /srv/caf\\\\Xe9\\\\X20x'\nalias work_api 'cd \\'/srv/api\\''\n"
+    );
+    assert_eq!(
+        text(Format::Gtk, &data),
+        "file:///srv/caf%E9%20x cafe\nfile:///srv/api api\n"
+    );
+    // CDPATH中无法表示，跳过并报告
+    let exported = export::export(Format::Cdpath, &data, None).unwrap();
+    assert_eq!(exported.text, "/srv/api\n");
+    assert_eq!(
+        exported.skipped,
+        [Skipped {
+            link: "work/cafe".to_string(),
+            reason: "path is not valid UTF-8".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn unresolved_and_repo_links_are_skipped() {
+    let data = data_with(&[
+        ("unset", Path::new("${DIR_LINK_TEST_UNSET}/x")),
+        ("repo", Path::new(":/docs")),
+        ("root", Path::new("${GIT_ROOT}/src")),
+        ("colon", Path::new("/srv/a:b")),
+        ("api", Path::new("/srv/api")),
+    ]);
+    let reasons = |format| {
+        export::export(format, &data, None)
+            .unwrap()
+            .skipped
+            .into_iter()
+            .map(|skipped| (skipped.link, skipped.reason))
+            .collect::<Vec<_>>()
+    };
+    let skipped = [
+        (
+            "work/unset",
+            "environment variable `DIR_LINK_TEST_UNSET` is not set",
+        ),
+        ("work/repo", "path depends on the current git repository"),
+        ("work/root", "path depends on the current git repository"),
+    ]
+    .map(|(link, reason)| (link.to_string(), reason.to_string()));
+    assert_eq!(
+        text(Format::Hash, &data),
+        "hash -d work_colon='/srv/a:b'\nhash -d work_api='/srv/api'\n"
+    );
+    assert_eq!(reasons(Format::Hash), skipped);
+
+    // CDPATH中的`:`是分隔符
+    let exported = export::export(Format::Cdpath, &data, None).unwrap();
+    assert_eq!(exported.text, "/srv/api\n");
+    let mut expected = skipped.to_vec();
+    expected.push(("work/colon".to_string(), "path contains `:`".to_string()));
+    assert_eq!(reasons(Format::Cdpath), expected);
+}
@@ -0,0 +1,127 @@
+use std::path::PathBuf;
+
+use dir_link::import::{self, Entry, Source};
+
+fn entry(name: Option<&str>, path: &str) -> Entry {
+    Entry {
+        name: name.map(String::from),
+        path: PathBuf::from(path),
+    }
+}
+
+fn paths(entries: &[Entry]) -> Vec<&str> {
+    entries
+        .iter()
+        .map(|entry| entry.path.to_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn zoxide_keeps_query_order() {
+    let text = "  12.5 /srv/api\n   3.0 /srv/my docs\n";
+    assert_eq!(
+        paths(&import::parse_zoxide(text)),
+        ["/srv/api", "/srv/my docs"]
+    );
+}
+
+#[test]
+fn autojump_sorts_by_weight() {
+    let text = "10.0\t/srv/low\n42.5\t/srv/high\nbroken line\n";
+    assert_eq!(
+        paths(&import::parse_autojump(text)),
+        ["/srv/high", "/srv/low"]
+    );
+}
+
+#[test]
+fn fasd_sorts_by_rank() {
+    let text = "/srv/low|1.5|1700000000\n/srv/a|b|9|1700000000\nnot fasd\n";
+    assert_eq!(paths(&import::parse_fasd(text)), ["/srv/a|b", "/srv/low"]);
+}
+
+#[test]
+fn marks_skip_last_position() {
+    let text = "a:/srv/api\n':/srv/previous\nd:/srv/docs\n";
+    assert_eq!(
+        import::parse_marks(text),
+        [entry(Some("a"), "/srv/api"), entry(Some("d"), "/srv/docs")]
+    );
+    assert_eq!(Source::Ranger.parse(text), Source::Lf.parse(text));
+}
+
+#[test]
+fn nnn_splits_bookmarks_variable() {
+    assert_eq!(
+        import::parse_nnn("a:/srv/api; d:/srv/docs"),
+        [entry(Some("a"), "/srv/api"), entry(Some("d"), "/srv/docs")]
+    );
+}
+
+#[test]
+fn gtk_decodes_uris_and_labels() {
+    let text = "file:///srv/my%20docs Docs\nfile:///srv/api\nsftp://host/srv\n";
+    assert_eq!(
+        import::parse_gtk(text),
+        [entry(Some("Docs"), "/srv/my docs"), entry(None, "/srv/api")]
+    );
+}
+
+#[test]
+fn zsh_reads_only_named_directories() {
+    let text = "\
+HISTFILE=~/.zsh_history
+EDITOR=vim
+PATH=/usr/local/bin:$PATH
+# hash -d old=/srv/old
+hash -d api=/srv/api docs='/srv/docs'
+  hash -d work=\"/srv/work\"
+hash -d
+";
+    assert_eq!(
+        import::parse_zsh_hash(text),
+        [
+            entry(Some("api"), "/srv/api"),
+            entry(Some("docs"), "/srv/docs"),
+            entry(Some("work"), "/srv/work"),
+        ]
+    );
+}
+
+#[test]
+fn zsh_keeps_quoted_spaces_together() {
+    let text = r#"
+hash -d proj='/path with spaces' 'tmp=/tmp/a b'
+hash -d notes="/srv/my \"notes\"" esc=/srv/a\ b # comment=/ignored
+hash  -d  extra=/srv/extra
+"#;
+    assert_eq!(
+        import::parse_zsh_hash(text),
+        [
+            entry(Some("proj"), "/path with spaces"),
+            entry(Some("tmp"), "/tmp/a b"),
+            entry(Some("notes"), "/srv/my \"notes\""),
+            entry(Some("esc"), "/srv/a b"),
+            entry(Some("extra"), "/srv/extra"),
+        ]
+    );
+}
+
+#[test]
+fn to_link_dir_dedups_identifiers_and_paths() {
+    let entries = vec![
+        entry(None, "/srv/a/api"),
+        entry(None, "/srv/b/api"),
+        entry(None, "/srv/a/api"),
+        entry(Some("rel"), "relative/path"),
+    ];
+    let imported = import::to_link_dir("zoxide", entries).unwrap();
+    let links: Vec<_> = imported
+        .dir
+        .iter()
+        .map(|link| (link.identifier(), link.path().to_str().unwrap()))
+        .collect();
+    assert_eq!(links, [("api", "/srv/a/api"), ("api-2", "/srv/b/api")]);
+    // 无法转换的书签返回给调用者，重复的路径不算
+    assert_eq!(imported.skipped, [entry(Some("rel"), "relative/path")]);
+}
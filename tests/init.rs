@@ -0,0 +1,39 @@
+use dir_link::init::{self, Shell};
+
+const SHELLS: [Shell; 6] = [
+    Shell::Bash,
+    Shell::Zsh,
+    Shell::Fish,
+    Shell::Nushell,
+    Shell::Elvish,
+    Shell::Xonsh,
+];
+
+#[test]
+fn every_template_substitutes_command() {
+    for shell in SHELLS {
+        let script = init::init(shell, "jj");
+        assert!(!script.contains("{{"), "{shell:?}: {script}");
+        // 定义的命令和补全都使用替换后的名称
+        let defined = match shell {
+            Shell::Bash | Shell::Zsh => "function jj()",
+            Shell::Fish => "function jj",
+            Shell::Nushell => "def --env --wrapped jj [",
+            Shell::Elvish => "fn jj {",
+            Shell::Xonsh => "aliases[\"jj\"]",
+        };
+        assert!(script.contains(defined), "{shell:?}: {script}");
+        assert!(script.matches("jj").count() >= 2, "{shell:?}: {script}");
+    }
+}
+
+#[test]
+fn command_name_is_not_hardcoded() {
+    for shell in SHELLS {
+        assert_ne!(
+            init::init(shell, "jj"),
+            init::init(shell, "kk"),
+            "{shell:?}"
+        );
+    }
+}
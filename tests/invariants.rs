@@ -0,0 +1,249 @@
+use std::path::Path;
+
+use dir_link::data::{dir::LinkDir, dirset::LinkDirSet, link::Link, origin::Origin};
+use proptest::{prelude::*, sample::Index};
+
+// 标识符只取很少的几个值，这样操作序列中经常出现重复和空的标识符
+fn identifier() -> impl Strategy<Value = String> {
+    "[ab]{0,2}"
+}
+
+#[derive(Debug, Clone)]
+enum Op {
+    PushDir(String),
+    RemoveDir(Index),
+    RenameDir(Index, String),
+    SwapDirs(Index, Index),
+    PushLink(Index, String),
+    RemoveLink(Index, Index),
+    RenameLink(Index, Index, String),
+    SwapLinks(Index, Index, Index),
+    SetLinks(Index, Vec<String>),
+    MergeLayer(Vec<(String, Vec<String>)>),
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let idx = any::<Index>;
+    prop_oneof![
+        identifier().prop_map(Op::PushDir),
+        idx().prop_map(Op::RemoveDir),
+        (idx(), identifier()).prop_map(|(i, s)| Op::RenameDir(i, s)),
+        (idx(), idx()).prop_map(|(a, b)| Op::SwapDirs(a, b)),
+        (idx(), identifier()).prop_map(|(i, s)| Op::PushLink(i, s)),
+        (idx(), idx()).prop_map(|(i, j)| Op::RemoveLink(i, j)),
+        (idx(), idx(), identifier()).prop_map(|(i, j, s)| Op::RenameLink(i, j, s)),
+        (idx(), idx(), idx()).prop_map(|(i, a, b)| Op::SwapLinks(i, a, b)),
+        (idx(), prop::collection::vec(identifier(), 0..4)).prop_map(|(i, v)| Op::SetLinks(i, v)),
+        prop::collection::vec(
+            (identifier(), prop::collection::vec(identifier(), 0..3)),
+            0..3
+        )
+        .prop_map(Op::MergeLayer),
+    ]
+}
+
+// 用最简单的方式描述期望的结果：文件夹和其中链接的标识符
+type Model = Vec<(String, Vec<String>)>;
+
+fn model_of(data: &LinkDirSet) -> Model {
+    data.iter()
+        .map(|dir| {
+            let links = dir.iter().map(|l| l.identifier().to_string()).collect();
+            (dir.identifier().to_string(), links)
+        })
+        .collect()
+}
+
+fn link(identifier: &str) -> Result<Link, dir_link::data::err::Error> {
+    Link::builder(identifier, Path::new("/tmp"))
+}
+
+fn has_duplicates(names: &[String]) -> bool {
+    names
+        .iter()
+        .enumerate()
+        .any(|(i, name)| names[..i].contains(name))
+}
+
+fn apply(data: &mut LinkDirSet, model: &mut Model, op: Op) {
+    match op {
+        Op::PushDir(name) => {
+            let ok = !name.is_empty() && model.iter().all(|(d, _)| *d != name);
+            let result = LinkDir::builder(&name).and_then(|dir| data.push(dir));
+            assert_eq!(result.is_ok(), ok);
+            if ok {
+                model.push((name, Vec::new()));
+            }
+        }
+        Op::RemoveDir(i) if !model.is_empty() => {
+            let i = i.index(model.len());
+            let removed = data.remove(i);
+            assert_eq!(removed.identifier(), model.remove(i).0);
+        }
+        Op::RenameDir(i, name) if !model.is_empty() => {
+            let i = i.index(model.len());
+            let ok = !name.is_empty() && model.iter().all(|(d, _)| *d != name);
+            assert_eq!(data.rename(i, &name).is_ok(), ok);
+            if ok {
+                model[i].0 = name;
+            }
+        }
+        Op::SwapDirs(a, b) if !model.is_empty() => {
+            let (a, b) = (a.index(model.len()), b.index(model.len()));
+            data.swap(a, b);
+            model.swap(a, b);
+        }
+        Op::PushLink(i, name) if !model.is_empty() => {
+            let i = i.index(model.len());
+            let ok = !name.is_empty() && !model[i].1.contains(&name);
+            let result = link(&name).and_then(|l| data.dir_mut(i).push(l));
+            assert_eq!(result.is_ok(), ok);
+            if ok {
+                model[i].1.push(name);
+            }
+        }
+        Op::RemoveLink(i, j) if !model.is_empty() => {
+            let i = i.index(model.len());
+            if !model[i].1.is_empty() {
+                let j = j.index(model[i].1.len());
+                let removed = data.dir_mut(i).remove(j);
+                assert_eq!(removed.identifier(), model[i].1.remove(j));
+            }
+        }
+        Op::RenameLink(i, j, name) if !model.is_empty() => {
+            let i = i.index(model.len());
+            if !model[i].1.is_empty() {
+                let j = j.index(model[i].1.len());
+                let ok = !name.is_empty() && !model[i].1.contains(&name);
+                assert_eq!(data.dir_mut(i).rename(j, &name).is_ok(), ok);
+                if ok {
+                    model[i].1[j] = name;
+                }
+            }
+        }
+        Op::SwapLinks(i, a, b) if !model.is_empty() => {
+            let i = i.index(model.len());
+            if !model[i].1.is_empty() {
+                let len = model[i].1.len();
+                let (a, b) = (a.index(len), b.index(len));
+                data.dir_mut(i).swap(a, b);
+                model[i].1.swap(a, b);
+            }
+        }
+        Op::SetLinks(i, names) if !model.is_empty() => {
+            let i = i.index(model.len());
+            let Ok(links) = names.iter().map(|name| link(name)).collect() else {
+                return;
+            };
+            let ok = !has_duplicates(&names);
+            assert_eq!(data.dir_mut(i).set_links(links).is_ok(), ok);
+            if ok {
+                model[i].1 = names;
+            }
+        }
+        Op::MergeLayer(dirs) => {
+            let mut layer = LinkDirSet::new();
+            for (name, links) in dirs {
+                let Ok(mut dir) = LinkDir::builder(&name) else {
+                    continue;
+                };
+                for l in links.iter().filter_map(|l| link(l).ok()) {
+                    let _ = dir.push(l);
+                }
+                let _ = layer.push(dir);
+            }
+            for (name, links) in model_of(&layer) {
+                match model.iter_mut().find(|(d, _)| *d == name) {
+                    Some((_, existing)) => {
+                        for l in links {
+                            if !existing.contains(&l) {
+                                existing.push(l);
+                            }
+                        }
+                    }
+                    None => model.push((name, links)),
+                }
+            }
+            data.merge_layer(layer, Origin::Team);
+        }
+        _ => {}
+    }
+}
+
+proptest! {
+    #[test]
+    fn operations_keep_sets_in_sync(ops in prop::collection::vec(op(), 0..64)) {
+        let mut data = LinkDirSet::new();
+        let mut model = Model::new();
+        for op in ops {
+            apply(&mut data, &mut model, op);
+            prop_assert!(data.is_consistent());
+            prop_assert_eq!(model_of(&data), model.clone());
+        }
+    }
+
+    #[test]
+    fn personal_data_round_trips(ops in prop::collection::vec(op(), 0..32)) {
+        let mut data = LinkDirSet::new();
+        let mut model = Model::new();
+        for op in ops {
+            apply(&mut data, &mut model, op);
+        }
+        let personal = data.personal();
+        prop_assert!(personal.is_consistent());
+        let json = serde_json::to_string(&personal).unwrap();
+        let parsed: LinkDirSet = serde_json::from_str(&json).unwrap();
+        prop_assert!(parsed.is_consistent());
+        prop_assert_eq!(parsed, personal);
+    }
+
+    #[test]
+    fn deserialize_rejects_duplicates(
+        dirs in prop::collection::vec(
+            (identifier(), prop::collection::vec(identifier(), 0..4)),
+            0..4,
+        )
+    ) {
+        let json = serde_json::to_string(
+            &dirs
+                .iter()
+                .map(|(name, links)| {
+                    let links: Vec<_> = links
+                        .iter()
+                        .map(|l| serde_json::json!({ "identifier": l, "path": "/tmp" }))
+                        .collect();
+                    serde_json::json!({ "identifier": name, "links": links })
+                })
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let names: Vec<_> = dirs.iter().map(|(name, _)| name.clone()).collect();
+        let valid = !has_duplicates(&names)
+            && dirs.iter().all(|(name, links)| {
+                !name.is_empty() && !has_duplicates(links) && links.iter().all(|l| !l.is_empty())
+            });
+        match serde_json::from_str::<LinkDirSet>(&json) {
+            Ok(data) => {
+                prop_assert!(valid);
+                prop_assert!(data.is_consistent());
+                prop_assert_eq!(model_of(&data), dirs);
+            }
+            Err(_) => prop_assert!(!valid),
+        }
+    }
+
+    #[test]
+    fn deserialize_checks_paths_like_builder(
+        identifier in identifier(),
+        path in prop::sample::select(vec![
+            "/tmp", "relative/path", ".", "", "~/src", "${HOME}/src", ":/docs", "~user",
+        ]),
+    ) {
+        let json = serde_json::json!({ "identifier": identifier, "path": path }).to_string();
+        let built = Link::builder(&identifier, Path::new(path));
+        match serde_json::from_str::<Link>(&json) {
+            Ok(link) => prop_assert_eq!(Ok(link), built),
+            Err(_) => prop_assert!(built.is_err()),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use std::{env, path::PathBuf};
+
+use dir_link::{
+    log,
+    profile::{DEFAULT_PROFILE, DataSource},
+    temp::PrivateDir,
+};
+
+#[test]
+fn logs_follow_the_data_file() {
+    let source = DataSource::file(PathBuf::from("/srv/links/data.json"));
+    assert_eq!(log::log_dir(&source), PathBuf::from("/srv/links/logs"));
+
+    // 相对路径按照启动时的当前目录转换，不会跟随之后的当前目录
+    let cwd = env::current_dir().unwrap();
+    let source = DataSource::file(PathBuf::from("data.json"));
+    assert_eq!(log::log_dir(&source), cwd.join("logs"));
+    let source = DataSource::file(PathBuf::from("links/data.json"));
+    assert_eq!(log::log_dir(&source), cwd.join("links/logs"));
+}
+
+#[test]
+fn profiles_log_to_the_data_dir() {
+    let dir = PrivateDir::new("log").unwrap();
+    for name in [DEFAULT_PROFILE, "work"] {
+        let source = DataSource::profile_in(dir.path(), name).unwrap();
+        assert_eq!(log::log_dir(&source), dir.path().join("logs"), "{name}");
+    }
+}
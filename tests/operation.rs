@@ -0,0 +1,120 @@
+mod common;
+
+use std::path::Path;
+
+use common::sample;
+
+use dir_link::{
+    app::operation::{self, Applied, OperationError, PendingOperation},
+    data::{dir::LinkDir, dirset::LinkDirSet, link::Link, origin::Origin},
+};
+
+fn dir(folder: &str, links: &[&str]) -> LinkDir {
+    let mut dir = LinkDir::builder(folder).unwrap();
+    for identifier in links {
+        let path = Path::new("/srv").join(identifier);
+        dir.push(Link::builder(identifier, &path).unwrap()).unwrap();
+    }
+    dir
+}
+
+fn delete_folder(folder: &str) -> PendingOperation {
+    PendingOperation::DeleteFolder {
+        folder: folder.to_string(),
+    }
+}
+
+fn delete_link(folder: &str, link: &str) -> PendingOperation {
+    PendingOperation::DeleteLink {
+        folder: folder.to_string(),
+        link: link.to_string(),
+    }
+}
+
+#[test]
+fn deletes_folder_by_identifier() {
+    let mut data = sample();
+    assert_eq!(
+        operation::apply(&mut data, &delete_folder("home")),
+        Ok(Applied::FolderRemoved(1))
+    );
+    assert!(data.find("home").is_none());
+    assert!(data.is_consistent());
+}
+
+#[test]
+fn deletes_link_by_identifier() {
+    let mut data = sample();
+    assert_eq!(
+        operation::apply(&mut data, &delete_link("work", "docs")),
+        Ok(Applied::LinkRemoved { folder: 0, link: 1 })
+    );
+    assert!(data.find_link("work/docs").is_none());
+    assert!(data.find_link("work/api").is_some());
+}
+
+#[test]
+fn targets_follow_identifiers_after_data_changes() {
+    let mut data = sample();
+    let pending = delete_link("work", "docs");
+    // 确认之前其他的修改改变了下标
+    data.swap(0, 1);
+    data.dir_mut(1).swap(0, 1);
+    assert_eq!(
+        operation::apply(&mut data, &pending),
+        Ok(Applied::LinkRemoved { folder: 1, link: 0 })
+    );
+    assert_eq!(data.find("work").unwrap()[0].identifier(), "api");
+}
+
+#[test]
+fn stale_targets_are_not_found() {
+    let mut data = sample();
+    let remove_folder = delete_folder("home");
+    let remove_link = delete_link("work", "docs");
+    data.rename(1, "house").unwrap();
+    data.dir_mut(0).rename(1, "manual").unwrap();
+    let before = data.link_names().collect::<Vec<_>>();
+
+    assert_eq!(
+        operation::apply(&mut data, &remove_folder),
+        Err(OperationError::FolderNotFound("home".to_string()))
+    );
+    assert_eq!(
+        operation::apply(&mut data, &remove_link),
+        Err(OperationError::LinkNotFound(
+            "work".to_string(),
+            "docs".to_string()
+        ))
+    );
+    assert_eq!(
+        operation::apply(&mut data, &delete_link("gone", "docs")),
+        Err(OperationError::FolderNotFound("gone".to_string()))
+    );
+    assert_eq!(data.link_names().collect::<Vec<_>>(), before);
+}
+
+#[test]
+fn shared_links_are_read_only() {
+    let mut data = sample();
+    let mut team = LinkDirSet::new();
+    team.push(dir("work", &["wiki"])).unwrap();
+    team.push(dir("team", &["ci"])).unwrap();
+    data.merge_layer(team, Origin::Team);
+
+    assert_eq!(
+        operation::apply(&mut data, &delete_link("work", "wiki")),
+        Err(OperationError::ReadOnly("work/wiki".to_string()))
+    );
+    // 包含共享链接的文件夹不能删除
+    assert_eq!(
+        operation::apply(&mut data, &delete_folder("work")),
+        Err(OperationError::ReadOnly("work".to_string()))
+    );
+    assert_eq!(
+        operation::apply(&mut data, &delete_folder("team")),
+        Err(OperationError::ReadOnly("team".to_string()))
+    );
+    // 同一个文件夹中的个人链接仍然可以删除
+    assert!(operation::apply(&mut data, &delete_link("work", "api")).is_ok());
+}
@@ -0,0 +1,105 @@
+#![cfg(unix)]
+
+use std::{
+    ffi::{OsStr, OsString},
+    os::unix::ffi::{OsStrExt, OsStringExt},
+    path::{Path, PathBuf},
+};
+
+use dir_link::{
+    app::float::edit::LinkEditState,
+    data::{bulk, dir::LinkDir, dirset::LinkDirSet, escape, link, link::Link},
+};
+
+fn non_utf8_path() -> PathBuf {
+    PathBuf::from(OsString::from_vec(
+        b"/tmp/caf\xe9/\xff\xfe\\x41/\xe4\xb8\xad".to_vec(),
+    ))
+}
+
+fn data_with(path: &Path) -> LinkDirSet {
+    let mut dir = LinkDir::builder("folder").unwrap();
+    dir.push(Link::builder("link", path).unwrap()).unwrap();
+    let mut data = LinkDirSet::new();
+    data.push(dir).unwrap();
+    data
+}
+
+#[test]
+fn escape_round_trips_every_byte() {
+    let bytes: Vec<u8> = (0..=255).collect();
+    let path = OsStr::from_bytes(&bytes);
+    assert_eq!(escape::unescape(&escape::escape(path)), path);
+
+    for byte in 0..=255u8 {
+        let pair = [byte, b'\\', byte, b'x'];
+        let path = OsStr::from_bytes(&pair);
+        assert_eq!(escape::unescape(&escape::escape(path)), path);
+    }
+}
+
+#[test]
+fn escape_keeps_valid_utf8_readable() {
+    assert_eq!(escape::escape(OsStr::new("/tmp/中文 dir")), "/tmp/中文 dir");
+    assert_eq!(escape::escape(OsStr::new(r"/tmp/a\b")), r"/tmp/a\\b");
+    assert_eq!(
+        escape::escape(OsStr::from_bytes(b"/tmp/\xff")),
+        r"/tmp/\xFF"
+    );
+}
+
+#[test]
+fn unescape_keeps_unknown_backslashes() {
+    assert_eq!(escape::unescape(r"/tmp/\n\x4"), OsStr::new(r"/tmp/\n\x4"));
+    assert_eq!(escape::unescape(r"/tmp/\x41\\"), OsStr::new(r"/tmp/A\"));
+}
+
+#[test]
+fn utf8_path_is_saved_as_plain_string() {
+    let json = serde_json::to_string(&data_with(Path::new("/tmp/a"))).unwrap();
+    assert_eq!(
+        json,
+        r#"[{"identifier":"folder","links":[{"identifier":"link","path":"/tmp/a"}]}]"#
+    );
+}
+
+#[test]
+fn non_utf8_path_round_trips_through_json() {
+    let path = non_utf8_path();
+    let data = data_with(&path);
+    let json = serde_json::to_string(&data).unwrap();
+    assert!(json.contains(r#""path":{"encoded":"#));
+
+    let loaded: LinkDirSet = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded, data);
+    assert_eq!(loaded[0][0].path(), path);
+}
+
+#[test]
+fn edit_float_does_not_mangle_path() {
+    let path = non_utf8_path();
+    let state = LinkEditState::new(0, Some(0)).with_value("link", path.as_os_str());
+    let (_, value) = state.value();
+    assert_eq!(link::get_vaild_path(value).unwrap(), path);
+}
+
+#[test]
+fn bulk_edit_does_not_mangle_path() {
+    let path = non_utf8_path();
+    let data = data_with(&path);
+    let links = bulk::parse(&bulk::dump(&data[0])).unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].path(), path);
+}
+
+#[test]
+fn pasted_file_uri_keeps_non_utf8_bytes() {
+    assert_eq!(
+        link::clean_pasted_path("'file:///tmp/caf%E9/%FF%FE%5Cx41/%E4%B8%AD'\n"),
+        non_utf8_path()
+    );
+    assert_eq!(
+        link::clean_pasted_path("file://localhost/tmp/a%20b"),
+        Path::new("/tmp/a b")
+    );
+}
@@ -0,0 +1,90 @@
+use std::{env, path::PathBuf};
+
+use clap::Parser;
+use dir_link::{
+    Error,
+    cli::Cli,
+    profile::{self, DATA_ENV, DEFAULT_PROFILE, DataSource, PROFILE_ENV},
+};
+
+fn resolve(args: &[&str]) -> DataSource {
+    let cli = Cli::try_parse_from(["dir_link"].iter().chain(args)).unwrap();
+    DataSource::resolve(cli.data, cli.profile.as_deref()).unwrap()
+}
+
+fn profile_source(name: &str) -> DataSource {
+    DataSource::profile(name).unwrap()
+}
+
+#[test]
+fn resolve_prefers_data_then_profile() {
+    let file = DataSource::resolve(Some(PathBuf::from("/tmp/a.json")), Some("work")).unwrap();
+    assert_eq!(file, DataSource::file(PathBuf::from("/tmp/a.json")));
+    assert_eq!(file.profile_name(), None);
+
+    let work = DataSource::resolve(None, Some("work")).unwrap();
+    assert_eq!(work.profile_name(), Some("work"));
+    assert!(work.path().ends_with("profiles/work.json"));
+
+    let default = DataSource::resolve(None, None).unwrap();
+    assert_eq!(default.profile_name(), Some(DEFAULT_PROFILE));
+    assert!(default.path().ends_with("data.json"));
+}
+
+// 只有这个测试修改环境变量，同一个测试文件中的其他测试不读取这两个变量
+#[test]
+fn command_line_and_environment_precedence() {
+    // SAFETY: 同一个进程中没有其他线程读写这两个环境变量
+    unsafe {
+        env::remove_var(DATA_ENV);
+        env::remove_var(PROFILE_ENV);
+    }
+    assert_eq!(resolve(&[]), profile_source(DEFAULT_PROFILE));
+
+    unsafe { env::set_var(PROFILE_ENV, "env") };
+    assert_eq!(resolve(&[]), profile_source("env"));
+    assert_eq!(resolve(&["--profile", "flag"]), profile_source("flag"));
+
+    // 数据文件优先于任何配置名
+    unsafe { env::set_var(DATA_ENV, "/tmp/env.json") };
+    let env_file = DataSource::file(PathBuf::from("/tmp/env.json"));
+    assert_eq!(resolve(&[]), env_file);
+    assert_eq!(resolve(&["--profile", "flag"]), env_file);
+    assert_eq!(
+        resolve(&["--data", "/tmp/flag.json", "--profile", "flag"]),
+        DataSource::file(PathBuf::from("/tmp/flag.json"))
+    );
+    assert_eq!(DataSource::from_env().unwrap(), env_file);
+
+    unsafe {
+        env::remove_var(DATA_ENV);
+        env::remove_var(PROFILE_ENV);
+    }
+}
+
+#[test]
+fn invalid_profile_names_are_rejected() {
+    for name in ["", ".", "..", ".hidden", "a/b", "../a", "a\\b", "a b"] {
+        assert!(
+            matches!(DataSource::profile(name), Err(Error::InvalidProfile(n)) if n == name),
+            "{name:?}"
+        );
+        assert!(DataSource::resolve(None, Some(name)).is_err(), "{name:?}");
+    }
+    for name in ["work", "my-profile_2", "v1.0", "工作"] {
+        assert_eq!(profile_source(name).profile_name(), Some(name));
+    }
+}
+
+#[test]
+fn profile_paths() {
+    let dir = PathBuf::from("/data");
+    assert_eq!(
+        profile::profile_path(&dir, DEFAULT_PROFILE),
+        dir.join("data.json")
+    );
+    assert_eq!(
+        profile::profile_path(&dir, "work"),
+        dir.join("profiles/work.json")
+    );
+}
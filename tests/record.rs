@@ -0,0 +1,104 @@
+mod common;
+
+use std::fs;
+
+use common::sample;
+
+use dir_link::{
+    app::{
+        App,
+        data::{DataTransfer, RuntimeError},
+        event::ScriptedEvents,
+        record::{Recorder, Replay},
+    },
+    data::dir::LinkDir,
+    layer::Layers,
+    profile::{self, DataSource},
+    temp::PrivateDir,
+};
+use ratatui::{Terminal, backend::TestBackend};
+
+fn run(app: App) -> DataTransfer {
+    let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+    app.run(&mut terminal, RuntimeError::new(), DataTransfer::new())
+        .unwrap()
+}
+
+#[test]
+fn replay_reproduces_recorded_session() {
+    let dir = PrivateDir::new("record").unwrap();
+    let path = dir.path().join("events.jsonl");
+    let recorder = Recorder::create(ScriptedEvents::keys("\njxyq"), &path).unwrap();
+    let recorded = run(App::new(sample()).with_event_source(recorder));
+
+    let replay = Replay::open(&path).unwrap();
+    let replayed = run(App::new(sample()).with_event_source(replay));
+
+    assert_eq!(replayed.data, recorded.data);
+    assert_eq!(replayed.data.unwrap()[0].len(), 1);
+}
+
+#[test]
+fn replay_sandbox_copies_data_and_shared_files() {
+    let real = PrivateDir::new("record-real").unwrap();
+    let data = real.path().join("data.json");
+    let team = real.path().join("team.json");
+    fs::write(&data, "[]").unwrap();
+    fs::write(&team, "[]").unwrap();
+    let missing = real.path().join("missing.json");
+
+    let sandbox = PrivateDir::new("record-sandbox").unwrap();
+    let source = DataSource::file(data.clone())
+        .sandbox(sandbox.path())
+        .unwrap();
+    assert!(source.path().starts_with(sandbox.path()));
+    assert_eq!(fs::read_to_string(source.path()).unwrap(), "[]");
+    // 已经复制过时使用之前的副本
+    fs::write(source.path(), "[ ]").unwrap();
+    let again = DataSource::file(data.clone())
+        .sandbox(sandbox.path())
+        .unwrap();
+    assert_eq!(fs::read_to_string(again.path()).unwrap(), "[ ]");
+    assert_eq!(fs::read_to_string(&data).unwrap(), "[]");
+
+    let layers = Layers::new()
+        .with_team(vec![team.clone(), missing.clone()])
+        .sandbox(sandbox.path())
+        .unwrap();
+    assert!(layers.team()[0].starts_with(sandbox.path()));
+    assert_eq!(layers.team()[1], missing);
+}
+
+#[test]
+fn replay_sandbox_keeps_profiles_untouched() {
+    // 使用临时的数据目录代替用户真实的数据目录
+    let data_dir = PrivateDir::new("record-data-dir").unwrap();
+    let existing = profile::profile_path(data_dir.path(), "work");
+    fs::create_dir_all(existing.parent().unwrap()).unwrap();
+    fs::write(&existing, r#"[{"identifier":"team","links":[]}]"#).unwrap();
+    let sandbox = PrivateDir::new("record-profile").unwrap();
+
+    let source = DataSource::file(sandbox.path().join("file.json"));
+    let mut app = App::new(sample())
+        .with_source(source)
+        .with_data_dir(data_dir.path().to_path_buf())
+        .with_sandbox(sandbox.path().to_path_buf());
+
+    // 不存在的配置只在沙盒中创建
+    app.switch_profile("new");
+    let current = app.option.source.as_ref().unwrap();
+    assert_eq!(current.profile_name(), Some("new"));
+    assert!(current.path().starts_with(sandbox.path()));
+    assert!(current.path().is_file());
+    assert!(!profile::profile_path(data_dir.path(), "new").exists());
+
+    // 已有的配置复制到沙盒中，修改不会写回
+    app.switch_profile("work");
+    assert!(app.data.find("team").is_some());
+    app.data.push(LinkDir::builder("mine").unwrap()).unwrap();
+    app.switch_profile("new");
+    assert_eq!(
+        fs::read_to_string(&existing).unwrap(),
+        r#"[{"identifier":"team","links":[]}]"#
+    );
+}
@@ -0,0 +1,218 @@
+use std::{
+    fs::{self, File},
+    path::Path,
+};
+
+use dir_link::{Error, Store, error, layer::Layers, temp::PrivateDir};
+
+// 每个测试使用自己的目录，测试可以并行执行
+fn temp_dir(name: &str) -> PrivateDir {
+    PrivateDir::new(&format!("store-{name}")).unwrap()
+}
+
+fn identifiers(store: &Store) -> Vec<String> {
+    store
+        .links()
+        .map(|(dir, link)| format!("{}/{}", dir.identifier(), link.identifier()))
+        .collect()
+}
+
+#[test]
+fn open_creates_file_and_save_round_trips() {
+    let dir = temp_dir("round-trip");
+    let path = dir.path().join("data.json");
+
+    let mut store = Store::open(&path).unwrap();
+    assert!(path.is_file());
+    assert_eq!(store.folders().count(), 0);
+
+    store.add_folder("work").unwrap();
+    store
+        .add_link("work", "api", Path::new("/srv/api"))
+        .unwrap();
+    store.add_link("work", "home", Path::new("~/work")).unwrap();
+    store.rename_link("work", "api", "server").unwrap();
+    store.save().unwrap();
+
+    let store = Store::open(&path).unwrap();
+    assert_eq!(identifiers(&store), ["work/server", "work/home"]);
+    assert_eq!(store.link("work/server").unwrap().path(), "/srv/api");
+}
+
+#[test]
+fn query_by_path() {
+    let dir = temp_dir("query");
+    let mut store = Store::open(dir.path().join("data.json")).unwrap();
+    store.add_folder("a").unwrap();
+    store.add_folder("b").unwrap();
+    store.add_link("a", "x", Path::new("/srv")).unwrap();
+    store.add_link("b", "y", Path::new("/srv")).unwrap();
+    store.add_link("b", "z", Path::new("/tmp")).unwrap();
+
+    let found: Vec<_> = store
+        .find_path(Path::new("/srv"))
+        .map(|(dir, link)| (dir.identifier(), link.identifier()))
+        .collect();
+    assert_eq!(found, [("a", "x"), ("b", "y")]);
+    assert!(store.folder("b").is_some());
+    assert!(store.link("b/x").is_none());
+}
+
+#[test]
+fn mutations_are_validated() {
+    let dir = temp_dir("validate");
+    let mut store = Store::open(dir.path().join("data.json")).unwrap();
+    store.add_folder("work").unwrap();
+    store.add_link("work", "api", Path::new("/srv")).unwrap();
+
+    let err = store
+        .add_link("work", "api", Path::new("/tmp"))
+        .unwrap_err();
+    assert_eq!(err.exit_code(), error::EXIT_INVALID);
+    assert!(err.to_string().contains("`api`"));
+
+    let err = store
+        .add_link("work", "rel", Path::new("relative"))
+        .unwrap_err();
+    assert!(matches!(err, Error::Invalid(_)));
+
+    let err = store.remove_link("none", "api").unwrap_err();
+    assert!(matches!(err, Error::NotFound { kind: "folder", .. }));
+    let err = store.relink("work", "none", Path::new("/")).unwrap_err();
+    assert_eq!(err.exit_code(), error::EXIT_NOT_FOUND);
+
+    assert_eq!(
+        store.remove_link("work", "api").unwrap().identifier(),
+        "api"
+    );
+    assert_eq!(store.remove_folder("work").unwrap().len(), 0);
+}
+
+#[test]
+fn shared_links_are_read_only_and_not_saved() {
+    let dir = temp_dir("shared");
+    let team = dir.path().join("team.json");
+    fs::write(
+        &team,
+        r#"[{"identifier":"team","links":[{"identifier":"ci","path":"/ci"}]}]"#,
+    )
+    .unwrap();
+    let path = dir.path().join("data.json");
+
+    let mut store = Store::open(&path).unwrap();
+    let layers = Layers::new().with_team(vec![team]);
+    assert!(store.merge_layers(&layers).is_empty());
+    assert_eq!(identifiers(&store), ["team/ci"]);
+
+    let err = store.rename_link("team", "ci", "build").unwrap_err();
+    assert!(matches!(err, Error::ReadOnly { .. }));
+    assert!(matches!(
+        store.remove_folder("team"),
+        Err(Error::ReadOnly { .. })
+    ));
+    assert!(store.add_link("team", "mine", Path::new("/")).is_err());
+
+    store.add_folder("mine").unwrap();
+    store.save().unwrap();
+    let store = Store::open(&path).unwrap();
+    let folders: Vec<_> = store.folders().map(|dir| dir.identifier()).collect();
+    assert_eq!(folders, ["mine"]);
+}
+
+#[test]
+fn corrupt_data_is_reported() {
+    let dir = temp_dir("corrupt");
+    let path = dir.path().join("data.json");
+    fs::write(&path, "{not json").unwrap();
+
+    let err = Store::open(&path).unwrap_err();
+    assert!(matches!(&err, Error::CorruptData { path: p, .. } if *p == path));
+    assert_eq!(err.exit_code(), error::EXIT_CORRUPT_DATA);
+    // 无法解析的文件不会被覆盖
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{not json");
+}
+
+#[test]
+fn save_skips_unmodified_data() {
+    let dir = temp_dir("unmodified");
+    let path = dir.path().join("data.json");
+    // 手写的格式在没有修改时保持不变
+    let text = "[\n  {\"identifier\": \"work\", \"links\": []}\n]\n";
+    fs::write(&path, text).unwrap();
+
+    let mut store = Store::open(&path).unwrap();
+    store.save().unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), text);
+
+    store.add_link("work", "api", Path::new("/srv")).unwrap();
+    store.save().unwrap();
+    let store = Store::open(&path).unwrap();
+    assert_eq!(identifiers(&store), ["work/api"]);
+}
+
+#[test]
+fn open_existing_does_not_create_file() {
+    let dir = temp_dir("open-existing");
+    let path = dir.path().join("data.json");
+    let store = Store::open_existing(&path).unwrap();
+    assert_eq!(store.folders().count(), 0);
+    assert!(!path.exists());
+}
+
+#[test]
+fn save_refuses_to_overwrite_concurrent_changes() {
+    let dir = temp_dir("conflict");
+    let path = dir.path().join("data.json");
+    let mut first = Store::open(&path).unwrap();
+    let mut second = Store::open(&path).unwrap();
+
+    second.add_folder("home").unwrap();
+    second.save().unwrap();
+
+    first.add_folder("work").unwrap();
+    let err = first.save().unwrap_err();
+    assert!(matches!(err, Error::Conflict { .. }));
+    assert_eq!(err.exit_code(), error::EXIT_IO);
+    assert_eq!(Store::open(&path).unwrap().folders().count(), 1);
+
+    // 重新读取之后可以继续修改
+    first.reload().unwrap();
+    first.add_folder("work").unwrap();
+    first.save().unwrap();
+    // 保存之后的修改仍然以自己写入的内容为准
+    first.add_folder("misc").unwrap();
+    first.save().unwrap();
+    let folders: Vec<_> = Store::open(&path)
+        .unwrap()
+        .folders()
+        .map(|dir| dir.identifier().to_string())
+        .collect();
+    assert_eq!(folders, ["home", "work", "misc"]);
+
+    // 读取时不存在的数据文件被其他进程创建
+    let missing = dir.path().join("missing.json");
+    let mut store = Store::open_existing(&missing).unwrap();
+    Store::open(&missing).unwrap();
+    store.add_folder("work").unwrap();
+    assert!(matches!(store.save(), Err(Error::Conflict { .. })));
+}
+
+#[test]
+fn save_fails_while_lock_is_held() {
+    let dir = temp_dir("locked");
+    let path = dir.path().join("data.json");
+    let mut store = Store::open(&path).unwrap();
+    store.add_folder("work").unwrap();
+
+    // 模拟另一个进程正在读写数据文件
+    let lock = File::create(dir.path().join("data.json.lock")).unwrap();
+    lock.lock().unwrap();
+    let err = store.save().unwrap_err();
+    assert!(matches!(&err, Error::Locked { path: p } if *p == path));
+    assert_eq!(err.exit_code(), error::EXIT_IO);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[]");
+
+    drop(lock);
+    store.save().unwrap();
+    assert_eq!(Store::open(&path).unwrap().folders().count(), 1);
+}
@@ -0,0 +1,28 @@
+use std::{fs, io::Write};
+
+use dir_link::temp::PrivateDir;
+
+#[test]
+fn private_dir_creates_new_files_and_cleans_up() {
+    let dir = PrivateDir::new("test").unwrap();
+    let other = PrivateDir::new("test").unwrap();
+    assert_ne!(dir.path(), other.path());
+
+    let (mut file, path) = dir.create_file("links.txt").unwrap();
+    file.write_all(b"api\t/srv/api\n").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "api\t/srv/api\n");
+    // 已经存在的文件不会被覆盖
+    assert!(dir.create_file("links.txt").is_err());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = |path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(dir.path()), 0o700);
+        assert_eq!(mode(&path), 0o600);
+    }
+
+    let root = dir.path().to_path_buf();
+    drop(dir);
+    assert!(!root.exists());
+}
@@ -0,0 +1,340 @@
+mod common;
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process,
+};
+
+use common::sample;
+use dir_link::{
+    Error,
+    app::{
+        App,
+        data::{Config, DataTransfer, RuntimeError},
+        event::{AppEvent, ScriptedEvents, TaskResult, key},
+        state::AppState,
+        worker::{Preview, Query, QueryKind, QueryResult},
+    },
+    data::{dir::LinkDir, dirset::LinkDirSet, link::Link},
+    import::{self, Entry},
+    layer::Layers,
+    profile::DataSource,
+    protocol::Action,
+    term::ViewportMode,
+};
+use ratatui::{Terminal, backend::TestBackend, buffer::Buffer, crossterm::event::KeyCode};
+
+fn terminal() -> Terminal<TestBackend> {
+    sized_terminal(60, 12)
+}
+
+fn sized_terminal(width: u16, height: u16) -> Terminal<TestBackend> {
+    Terminal::new(TestBackend::new(width, height)).unwrap()
+}
+
+/// 运行到脚本退出为止
+fn run(keys: &str) -> io::Result<DataTransfer> {
+    let app = App::new(sample()).with_event_source(ScriptedEvents::keys(keys));
+    app.run(&mut terminal(), RuntimeError::new(), DataTransfer::new())
+}
+
+fn press(app: &mut App, keys: &str) {
+    for c in keys.chars() {
+        let code = match c {
+            '\n' => KeyCode::Enter,
+            '\x1b' => KeyCode::Esc,
+            c => KeyCode::Char(c),
+        };
+        app.handle_event(AppEvent::Terminal(key(code)));
+    }
+}
+
+/// 只比较字符，不比较样式
+fn lines(buffer: &Buffer) -> Vec<String> {
+    (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect()
+        })
+        .collect()
+}
+
+fn render(app: &mut App) -> Vec<String> {
+    render_with(app, &mut terminal())
+}
+
+fn render_with(app: &mut App, terminal: &mut Terminal<TestBackend>) -> Vec<String> {
+    app.draw(terminal).unwrap();
+    lines(terminal.backend().buffer())
+}
+
+fn identifiers(dir: &LinkDir) -> Vec<&str> {
+    dir.iter().map(|link| link.identifier()).collect()
+}
+
+#[test]
+fn renders_folders_and_links() {
+    let mut app = App::new(sample());
+    assert_eq!(
+        render(&mut app),
+        [
+            "╭Dir Link──────────────────────────────────────────────────╮",
+            "│work             │Name         Path                       │",
+            "│home             │api          / (checking…)              │",
+            "│                 │docs         /tmp (checking…)           │",
+            "│                 │                                        │",
+            "│                 │                                        │",
+            "│                 │                                        │",
+            "│                 │                                        │",
+            "│                 │                                        │",
+            "│                 │                                        │",
+            "│                 │                                        │",
+            "╰──────────────────────────────────────────────────────────╯",
+        ]
+    );
+}
+
+#[test]
+fn query_results_replace_checking_state() {
+    let mut app = App::new(sample());
+    render(&mut app);
+    app.handle_event(AppEvent::Tasks(
+        ["/", "/tmp"]
+            .map(|path| TaskResult::Query {
+                query: Query::new(PathBuf::from(path), QueryKind::Exists),
+                result: QueryResult::Exists(true),
+            })
+            .into(),
+    ));
+    let screen = render(&mut app);
+    assert_eq!(
+        screen[2],
+        "│home             │api          /                          │"
+    );
+    assert_eq!(
+        screen[3],
+        "│                 │docs         /tmp                       │"
+    );
+
+    // 进入链接后在底部显示选中链接的目录信息
+    press(&mut app, "\n");
+    let preview = Preview {
+        total: 2,
+        entries: vec!["bin".to_string(), "etc".to_string()],
+    };
+    for (kind, result) in [
+        (QueryKind::Preview, QueryResult::Preview(Some(preview))),
+        (QueryKind::GitStatus, QueryResult::GitStatus(None)),
+    ] {
+        app.handle_event(AppEvent::Tasks(vec![TaskResult::Query {
+            query: Query::new(PathBuf::from("/"), kind),
+            result,
+        }]));
+    }
+    let screen = render(&mut app);
+    assert_eq!(
+        screen[11],
+        "╰─ 2 entries: bin, etc · checking… ────────────────────────╯"
+    );
+}
+
+#[test]
+fn repo_links_use_cached_repo_root() {
+    // 绘制时不查找`.git`，只使用启动时找到的仓库
+    let mut app = App::new(common::data(&[("repo", &[("docs", ":/docs")])]));
+    app.cache.repo_root = Some(PathBuf::from("/srv/repo"));
+    assert_eq!(
+        render(&mut app)[2],
+        "│                 │docs         :/docs → /srv/repo/docs (ch│"
+    );
+    app.cache.repo_root = None;
+    assert_eq!(
+        render(&mut app)[2],
+        "│                 │docs         :/docs (inactive)          │"
+    );
+}
+
+#[test]
+fn quit_without_selecting() {
+    let transfer = run("q").unwrap();
+    assert_eq!(transfer.action(), None);
+    assert_eq!(transfer.data, Some(sample()));
+}
+
+#[test]
+fn select_link_in_second_folder() {
+    let transfer = run("j\n\n").unwrap();
+    assert_eq!(transfer.action(), Some(&Action::Cd("/".into())));
+}
+
+#[test]
+fn print_selected_link() {
+    let transfer = run("\njy").unwrap();
+    assert_eq!(transfer.action(), Some(&Action::Print("/tmp".into())));
+}
+
+#[test]
+fn delete_link_after_confirm() {
+    let data = run("\njxyq").unwrap().data.unwrap();
+    assert_eq!(identifiers(&data[0]), ["api"]);
+    assert_eq!(identifiers(&data[1]), ["conf"]);
+}
+
+#[test]
+fn cancel_delete_keeps_data() {
+    let data = run("\njxnq").unwrap().data.unwrap();
+    assert_eq!(data, sample());
+}
+
+#[test]
+fn delete_folder_after_confirm() {
+    let data = run("xyq").unwrap().data.unwrap();
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].identifier(), "home");
+}
+
+#[test]
+fn add_folder() {
+    let data = run("anew\nq").unwrap().data.unwrap();
+    assert_eq!(data.len(), 3);
+    assert_eq!(data[2].identifier(), "new");
+    assert!(data[2].is_empty());
+}
+
+#[test]
+fn confirm_float_is_rendered_on_top() {
+    let mut app = App::new(sample());
+    press(&mut app, "\nx");
+    // 确认框的宽度是终端的一半，太窄时提示会换行
+    let screen = render_with(&mut app, &mut sized_terminal(80, 20));
+    assert!(screen.iter().any(|line| line.contains("Confirm")));
+    assert!(
+        screen
+            .iter()
+            .any(|line| line.contains("Are you sure to DELETE this link?"))
+    );
+
+    press(&mut app, "n");
+    assert!(app.inspect_floats().is_empty());
+    assert!(!render(&mut app).iter().any(|line| line.contains("Confirm")));
+    assert_eq!(app.data, sample());
+}
+
+#[test]
+fn help_float_closes_before_quit() {
+    let mut app = App::new(sample());
+    press(&mut app, "?");
+    assert_eq!(app.inspect_floats().len(), 1);
+    // 帮助窗口打开时`q`只关闭帮助
+    press(&mut app, "q");
+    assert!(app.inspect_floats().is_empty());
+    assert!(!matches!(app.state, AppState::Quit(_)));
+}
+
+#[test]
+fn unfinished_script_is_an_error() {
+    let err = run("j").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn reload_keeps_selection_by_identifier() {
+    let mut app = App::new(sample());
+    // 选中`work`中的`docs`
+    press(&mut app, "\nj");
+
+    // 重新读取后文件夹和链接的顺序都改变了
+    app.reload_data(common::data(&[
+        ("home", &[("conf", "/")]),
+        ("work", &[("new", "/new"), ("docs", "/tmp"), ("api", "/")]),
+    ]));
+    assert!(app.state.is_link());
+    assert_eq!(app.state.folder_list_state().unwrap().selected(), Some(1));
+    assert_eq!(app.selected_link().unwrap().identifier(), "docs");
+
+    // 选中的文件夹已经被删除时回到第一个文件夹
+    app.reload_data(common::data(&[("home", &[])]));
+    assert!(app.state.is_folder());
+    assert_eq!(app.state.folder_list_state().unwrap().selected(), Some(0));
+}
+
+#[test]
+fn import_preview_reports_skipped_bookmarks() {
+    let entries = vec![
+        Entry {
+            name: None,
+            path: PathBuf::from("/srv/api"),
+        },
+        Entry {
+            name: Some("rel".to_string()),
+            path: PathBuf::from("relative"),
+        },
+    ];
+    let imported = import::to_link_dir("zoxide", entries).unwrap();
+    let mut app = App::new(sample()).with_import(imported);
+    let screen = render_with(&mut app, &mut sized_terminal(80, 20));
+    assert!(
+        screen
+            .iter()
+            .any(|line| line.contains("Import 1 Links, 1 skipped")),
+        "{screen:#?}"
+    );
+}
+
+#[test]
+fn long_link_list_scrolls_with_selection() {
+    let mut dir = LinkDir::builder("many").unwrap();
+    for i in 0..30 {
+        dir.push(Link::builder(&format!("l{i:02}"), Path::new("/")).unwrap())
+            .unwrap();
+    }
+    let mut data = LinkDirSet::new();
+    data.push(dir).unwrap();
+    let mut app = App::new(data);
+
+    // 表头下面有9行，选中第21个链接时滚动到刚好可见
+    press(&mut app, &format!("\n{}", "j".repeat(20)));
+    let screen = render(&mut app);
+    assert!(screen[2].contains("l12"), "{screen:#?}");
+    assert!(screen[10].contains("l20"), "{screen:#?}");
+
+    // 向上移动时选中的行仍然可见，不会滚动
+    press(&mut app, "kkk");
+    let screen = render(&mut app);
+    assert!(screen[2].contains("l12"), "{screen:#?}");
+    assert!(screen[7].contains("l17"), "{screen:#?}");
+}
+
+#[test]
+fn actions_without_result_file_are_rejected() {
+    let dir = env::temp_dir().join(format!("dir_link-ui-result-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("data.json");
+    fs::write(&path, serde_json::to_string(&sample()).unwrap()).unwrap();
+
+    let run_keys = |keys: &str| {
+        let config = Config {
+            path: None,
+            save: false,
+            viewport: ViewportMode::Fullscreen,
+            import: None,
+            layers: Layers::default(),
+            events: Some(Box::new(ScriptedEvents::keys(keys))),
+            sandbox: None,
+        };
+        dir_link::run_app(DataSource::file(path.clone()), &mut terminal(), config)
+    };
+    // 只有路径的操作输出到stdout，在目录中打开编辑器无法这样表示
+    assert_eq!(
+        run_keys("\ny").unwrap(),
+        Some(Action::Print(PathBuf::from("/")))
+    );
+    let err = run_keys("\no").unwrap_err();
+    assert!(matches!(err, Error::NeedsResultFile("open-editor")));
+    assert_eq!(err.exit_code(), dir_link::error::EXIT_USAGE);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,224 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, mpsc},
+    time::{Duration, Instant},
+};
+
+use dir_link::{
+    app::{
+        event::TaskResult,
+        worker::{
+            GitStatus, Preview, Query, QueryCache, QueryKind, QueryResult, QueryState, Size,
+            WorkerPool,
+        },
+    },
+    temp::PrivateDir,
+};
+
+fn exists(path: &str) -> Query {
+    Query::new(PathBuf::from(path), QueryKind::Exists)
+}
+
+#[test]
+fn cache_reports_checking_timed_out_and_done() {
+    let mut cache = QueryCache::new()
+        .with_timeout(Duration::from_secs(5))
+        .with_ttl(Duration::from_secs(30));
+    let query = exists("/tmp");
+    let start = Instant::now();
+
+    assert_eq!(cache.get(&query, start), QueryState::Checking);
+    assert!(cache.needs_query(&query, start));
+
+    cache.start(query.clone(), start);
+    assert_eq!(cache.get(&query, start), QueryState::Checking);
+    assert!(!cache.needs_query(&query, start));
+    let late = start + Duration::from_secs(5);
+    assert_eq!(cache.get(&query, late), QueryState::TimedOut);
+    // 同一个路径的其他查询不需要等待
+    let preview = Query::new(PathBuf::from("/tmp"), QueryKind::Preview);
+    assert!(cache.needs_query(&preview, start));
+    // 超时之后再过`ttl`才重新提交
+    assert!(!cache.needs_query(&query, late + Duration::from_secs(29)));
+    assert!(cache.needs_query(&query, late + Duration::from_secs(30)));
+
+    cache.finish(query.clone(), QueryResult::Exists(false), late);
+    assert_eq!(
+        cache.get(&query, late),
+        QueryState::Done(&QueryResult::Exists(false))
+    );
+    assert!(!cache.needs_query(&query, late));
+    let expired = late + Duration::from_secs(30);
+    assert!(cache.needs_query(&query, expired));
+
+    // 重新查询时仍然显示之前的结果
+    cache.start(query.clone(), expired);
+    assert_eq!(
+        cache.get(&query, expired + Duration::from_secs(10)),
+        QueryState::Done(&QueryResult::Exists(false))
+    );
+}
+
+#[test]
+fn pool_sends_results_through_channel() {
+    let temp = PrivateDir::new("worker-pool").unwrap();
+    let dir = temp.path().to_path_buf();
+    fs::write(dir.join("b.txt"), [0u8; 2048]).unwrap();
+    fs::write(dir.join("a.txt"), "a").unwrap();
+    fs::create_dir(dir.join("c")).unwrap();
+    fs::write(dir.join("c").join("d.txt"), "dd").unwrap();
+
+    let (sender, receiver) = mpsc::channel();
+    let pool = WorkerPool::new(2, sender);
+    let queries = [
+        Query::new(dir.clone(), QueryKind::Exists),
+        Query::new(dir.join("missing"), QueryKind::Exists),
+        Query::new(dir.clone(), QueryKind::Preview),
+        Query::new(dir.clone(), QueryKind::Size),
+    ];
+    for query in queries.iter().cloned() {
+        pool.submit(query);
+    }
+
+    let mut results = Vec::new();
+    for _ in 0..queries.len() {
+        let TaskResult::Query { query, result } =
+            receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        results.push((query, result));
+    }
+    let result = |query: &Query| {
+        results
+            .iter()
+            .find(|(q, _)| q == query)
+            .map(|(_, result)| result.clone())
+            .unwrap()
+    };
+    assert_eq!(result(&queries[0]), QueryResult::Exists(true));
+    assert_eq!(result(&queries[1]), QueryResult::Exists(false));
+    assert_eq!(
+        result(&queries[2]),
+        QueryResult::Preview(Some(Preview {
+            total: 3,
+            entries: vec!["a.txt".to_string(), "b.txt".to_string(), "c".to_string()],
+        }))
+    );
+    assert_eq!(
+        result(&queries[3]),
+        QueryResult::Size(Size {
+            bytes: 2051,
+            complete: true,
+        })
+    );
+}
+
+#[test]
+fn blocked_query_does_not_hold_other_paths() {
+    let (release, blocked) = mpsc::channel::<()>();
+    let blocked = Mutex::new(blocked);
+    let (sender, receiver) = mpsc::channel();
+    // `/slow`的大小一直等到测试放行，模拟卡住的网络文件系统
+    let pool = WorkerPool::with_runner(1, sender, move |query: &Query| {
+        if query.path == Path::new("/slow") && query.kind == QueryKind::Size {
+            blocked.lock().unwrap().recv().unwrap();
+        }
+        match query.kind {
+            QueryKind::Size => QueryResult::Size(Size {
+                bytes: 0,
+                complete: true,
+            }),
+            _ => QueryResult::Exists(true),
+        }
+    });
+    let mut cache = QueryCache::new()
+        .with_timeout(Duration::from_secs(5))
+        .with_ttl(Duration::from_secs(30));
+    let start = Instant::now();
+    let submit = |cache: &mut QueryCache, query: Query, now: Instant| {
+        if cache.needs_query(&query, now) {
+            cache.start(query.clone(), now);
+            pool.submit(query);
+        }
+    };
+
+    let slow = Query::new(PathBuf::from("/slow"), QueryKind::Size);
+    submit(&mut cache, slow.clone(), start);
+    // 慢的查询在另一组线程中执行，同一个路径上检查是否存在也不需要等待
+    let fast = exists("/slow");
+    submit(&mut cache, fast.clone(), start);
+    let TaskResult::Query { query, result } =
+        receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(query, fast);
+    cache.finish(query, result, start);
+
+    // 超时之后先显示超时，过了`ttl`重新提交一次
+    let timed_out = start + Duration::from_secs(5);
+    assert_eq!(cache.get(&slow, timed_out), QueryState::TimedOut);
+    submit(&mut cache, slow.clone(), timed_out);
+    let retry = start + Duration::from_secs(35);
+    assert!(cache.needs_query(&slow, retry));
+    submit(&mut cache, slow.clone(), retry);
+    // 重新提交的查询也卡住时，同一个路径不会再占用更多线程
+    let later = retry + Duration::from_secs(120);
+    assert_eq!(cache.get(&slow, later), QueryState::TimedOut);
+    assert!(!cache.needs_query(&slow, later));
+    assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+
+    // 两次提交都返回结果
+    for _ in 0..2 {
+        release.send(()).unwrap();
+        let TaskResult::Query { query, result } =
+            receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(query, slow);
+        cache.finish(query, result, later);
+    }
+    assert!(matches!(cache.get(&slow, later), QueryState::Done(_)));
+    assert!(!cache.needs_query(&slow, later));
+    assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+}
+
+#[test]
+fn git_status_parses_branch_and_changes() {
+    assert_eq!(
+        GitStatus::parse("## main...origin/main [ahead 1]\n M src/app.rs\n"),
+        Some(GitStatus {
+            branch: "main".to_string(),
+            dirty: true,
+        })
+    );
+    assert_eq!(
+        GitStatus::parse("## No commits yet on master\n"),
+        Some(GitStatus {
+            branch: "master".to_string(),
+            dirty: false,
+        })
+    );
+    assert_eq!(GitStatus::parse(""), None);
+}
+
+#[test]
+fn details_display() {
+    let preview = Preview {
+        total: 10,
+        entries: vec!["a".to_string(), "b".to_string()],
+    };
+    assert_eq!(preview.to_string(), "10 entries: a, b, …");
+    let status = GitStatus {
+        branch: "main".to_string(),
+        dirty: true,
+    };
+    assert_eq!(status.to_string(), "main*");
+    let size = Size {
+        bytes: 3 * 1024 * 1024 + 512 * 1024,
+        complete: false,
+    };
+    assert_eq!(size.to_string(), ">3.5 MiB");
+    assert_eq!(
+        Size {
+            bytes: 12,
+            complete: true
+        }
+        .to_string(),
+        "12 B"
+    );
+}